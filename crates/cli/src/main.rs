@@ -1,6 +1,12 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, ops::RangeInclusive, path::PathBuf, sync::Arc, time::Duration};
 
+use arc_swap::ArcSwap;
 use clap::{Arg, ArgAction, Command, command, value_parser};
+use minecrust_gateway::{
+    Config, ServerKey,
+    config::{self, AuthMode},
+};
+use minecrust_protocol::{Limits, datatype::TextComponent};
 use tokio::signal;
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -25,22 +31,185 @@ async fn main() {
                 .action(ArgAction::Append),
         )
         .subcommand(
-            Command::new("gateway").about("Runs the gateway.").arg(
-                Arg::new("addr")
-                    .value_parser(value_parser!(SocketAddr))
-                    .default_value("127.0.0.1:25565"),
-            ),
+            Command::new("gateway")
+                .about("Runs the gateway.")
+                .arg(
+                    Arg::new("addr")
+                        .value_parser(value_parser!(SocketAddr))
+                        .default_value("127.0.0.1:25565"),
+                )
+                .arg(
+                    Arg::new("motd")
+                        .long("motd")
+                        .help("server list description, in tag markup")
+                        .default_value(config::DEFAULT_DESCRIPTION),
+                )
+                .arg(
+                    Arg::new("max-players")
+                        .long("max-players")
+                        .help("maximum player count shown in the server list")
+                        .value_parser(value_parser!(i32))
+                        .default_value("20"),
+                )
+                .arg(
+                    Arg::new("supported-protocols")
+                        .long("supported-protocols")
+                        .help("protocol versions shown as compatible, e.g. 767-773")
+                        .value_parser(parse_protocol_range)
+                        .value_delimiter(',')
+                        .action(ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("offline")
+                        .long("offline")
+                        .help("skip encryption and authentication of players")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("session-server")
+                        .long("session-server")
+                        .help("base url of the session server used for authentication")
+                        .default_value(minecrust_gateway::DEFAULT_SESSION_SERVER),
+                )
+                .arg(
+                    Arg::new("session-timeout")
+                        .long("session-timeout")
+                        .help("seconds to wait for the session server before failing the login")
+                        .value_parser(value_parser!(u64))
+                        .default_value("5"),
+                )
+                .arg(
+                    Arg::new("prevent-proxy-connections")
+                        .long("prevent-proxy-connections")
+                        .help("reject players whose session was created from another ip")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("key")
+                        .long("key")
+                        .help(
+                            "path to the rsa private key, pem or der encoded, generated if absent",
+                        )
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("favicon")
+                        .long("favicon")
+                        .help("path to a 64x64 png server icon")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("lenient-framing")
+                        .long("lenient-framing")
+                        .help("only log compressed packets that violate the threshold rules")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("strict-decoding")
+                        .long("strict-decoding")
+                        .help("reject packets with bytes left over after decoding")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("shutdown-message")
+                        .long("shutdown-message")
+                        .help("disconnect reason sent to players on shutdown, in tag markup")
+                        .default_value(config::DEFAULT_SHUTDOWN_MESSAGE),
+                )
+                .arg(
+                    Arg::new("shutdown-timeout")
+                        .long("shutdown-timeout")
+                        .help("seconds to wait for connections to close before forcing them")
+                        .value_parser(value_parser!(u64))
+                        .default_value("10"),
+                ),
         )
         .get_matches();
 
     let task_tracker = TaskTracker::new();
     let cancellation_token = CancellationToken::new();
+    let shutdown_timeout;
 
     match matches.subcommand() {
         Some(("gateway", matches)) => {
             let addr = matches
                 .get_one::<SocketAddr>("addr")
                 .expect("addr is required");
+            shutdown_timeout = Duration::from_secs(
+                *matches
+                    .get_one::<u64>("shutdown-timeout")
+                    .expect("shutdown-timeout has a default"),
+            );
+
+            let favicon = match matches.get_one::<PathBuf>("favicon") {
+                Some(path) => match config::load_favicon(path) {
+                    Ok(favicon) => Some(favicon),
+                    Err(err) => {
+                        tracing::error!(?err, ?path, "loading favicon failed");
+                        return;
+                    }
+                },
+                None => None,
+            };
+            let key = match matches.get_one::<PathBuf>("key") {
+                Some(path) => ServerKey::load(path),
+                None => ServerKey::generate(),
+            };
+            let key = match key {
+                Ok(key) => Arc::new(key),
+                Err(err) => {
+                    tracing::error!(?err, "loading server key failed");
+                    return;
+                }
+            };
+            let config = Config {
+                description: TextComponent::from_markup(
+                    matches
+                        .get_one::<String>("motd")
+                        .expect("motd has a default"),
+                ),
+                max_players: *matches
+                    .get_one::<i32>("max-players")
+                    .expect("max-players has a default"),
+                favicon,
+                auth_mode: if matches.get_flag("offline") {
+                    AuthMode::Offline
+                } else {
+                    AuthMode::Online
+                },
+                session_server: matches
+                    .get_one::<String>("session-server")
+                    .expect("session-server has a default")
+                    .clone(),
+                session_timeout: Duration::from_secs(
+                    *matches
+                        .get_one::<u64>("session-timeout")
+                        .expect("session-timeout has a default"),
+                ),
+                prevent_proxy_connections: matches.get_flag("prevent-proxy-connections"),
+                lenient_framing: matches.get_flag("lenient-framing"),
+                limits: Limits {
+                    reject_trailing_bytes: matches.get_flag("strict-decoding"),
+                    ..Limits::DEFAULT
+                },
+                shutdown_message: TextComponent::from_markup(
+                    matches
+                        .get_one::<String>("shutdown-message")
+                        .expect("shutdown-message has a default"),
+                ),
+                ..Config::new(key)
+            };
+            let config = match matches.get_many::<RangeInclusive<i32>>("supported-protocols") {
+                Some(ranges) => Config {
+                    supported_protocols: ranges.cloned().collect(),
+                    ..config
+                },
+                None => config,
+            };
+            if let Err(err) = config.validate() {
+                tracing::error!(%err, "invalid configuration");
+                return;
+            }
 
             tracing::info!(?addr, "starting gateway");
 
@@ -48,6 +217,7 @@ async fn main() {
                 cancellation_token.clone(),
                 task_tracker.clone(),
                 *addr,
+                Arc::new(ArcSwap::from_pointee(config)),
             ));
 
             tokio::select! {
@@ -74,5 +244,24 @@ async fn main() {
     tracing::info!("shutting down, please wait");
     cancellation_token.cancel();
     task_tracker.close();
-    task_tracker.wait().await;
+    if tokio::time::timeout(shutdown_timeout, task_tracker.wait())
+        .await
+        .is_err()
+    {
+        // Remaining connections are aborted when the runtime is dropped.
+        tracing::warn!(
+            connections = task_tracker.len(),
+            "shutdown timed out, closing remaining connections"
+        );
+    }
+}
+
+fn parse_protocol_range(value: &str) -> Result<RangeInclusive<i32>, std::num::ParseIntError> {
+    Ok(match value.split_once('-') {
+        Some((start, end)) => start.trim().parse()?..=end.trim().parse()?,
+        None => {
+            let protocol = value.trim().parse()?;
+            protocol..=protocol
+        }
+    })
 }
@@ -1,8 +1,8 @@
-use std::io::copy;
+use std::io::{Read, copy};
 
 use bytes::{Buf, BufMut, BytesMut};
 use flate2::read::ZlibDecoder;
-use minecrust_protocol::datatype::var_int;
+use minecrust_protocol::{Limits, datatype::var_int};
 use tokio_util::codec::Decoder;
 
 use crate::{Error, crypto::Cfb8Cipher, packet::RawPacket};
@@ -13,6 +13,8 @@ pub struct PacketDecoder {
     cipher_cursor: usize,
     pending_frame_length: Option<usize>,
     threshold: Option<usize>,
+    lenient: bool,
+    limits: Limits,
 }
 
 impl PacketDecoder {
@@ -30,17 +32,22 @@ impl PacketDecoder {
         let buffer_before_size = src.remaining();
         match self.pending_frame_length {
             Some(length) => Ok(Some(length)),
-            None => match var_int::deserialize(src) {
+            None => match var_int::deserialize(src, &self.limits) {
                 // Deserialize only consumes on success
                 Ok(var_int) => {
-                    let var_int = var_int as usize;
-                    self.pending_frame_length = Some(var_int);
-
                     self.cipher_cursor = self
                         .cipher_cursor
                         .saturating_sub(buffer_before_size - src.remaining());
 
-                    Ok(Some(var_int))
+                    let length =
+                        usize::try_from(var_int).map_err(|_| Error::InvalidFrameLength(var_int))?;
+                    let max = self.limits.max_frame_length;
+                    if length > max {
+                        return Err(Error::FrameTooLarge { length, max });
+                    }
+                    self.pending_frame_length = Some(length);
+
+                    Ok(Some(length))
                 }
                 Err(minecrust_protocol::Error::TryGetError(_))
                 | Err(minecrust_protocol::Error::UnexpectedEof) => Ok(None), // this is not an error, we just wait for more bytes
@@ -49,17 +56,56 @@ impl PacketDecoder {
         }
     }
 
+    /// Fails with `err` unless lenient.
+    fn violation(&self, err: Error) -> Result<(), Error> {
+        if self.lenient {
+            tracing::debug!(%err, "accepting invalid compressed frame");
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+
     fn inflate(&mut self, frame: &mut BytesMut) -> Result<(), Error> {
-        if self.threshold.is_some() {
-            let data_length = var_int::deserialize(frame)?;
+        if let Some(threshold) = self.threshold {
+            let data_length = var_int::deserialize(frame, &self.limits)?;
+            let data_length =
+                usize::try_from(data_length).map_err(|_| Error::InvalidFrameLength(data_length))?;
 
+            if data_length == 0 && frame.remaining() >= threshold {
+                self.violation(Error::UncompressedAboveThreshold {
+                    length: frame.remaining(),
+                    threshold,
+                })?;
+            }
             if data_length > 0 {
                 tracing::trace!("received packet must be inflated");
+                if data_length < threshold {
+                    self.violation(Error::CompressedBelowThreshold {
+                        length: data_length,
+                        threshold,
+                    })?;
+                }
+                let max = self.limits.max_uncompressed_length;
+                if data_length > max {
+                    return Err(Error::PacketTooLarge {
+                        length: data_length,
+                        max,
+                    });
+                }
 
                 let deflated_buffer = frame.split();
                 let mut writer = frame.writer();
-                let mut decoder = ZlibDecoder::new(&*deflated_buffer);
-                copy(&mut decoder, &mut writer)?;
+                // Reading one byte past the declared length is enough to detect a mismatch
+                // without inflating a zip bomb.
+                let mut decoder = ZlibDecoder::new(&*deflated_buffer).take(data_length as u64 + 1);
+                let actual = copy(&mut decoder, &mut writer)? as usize;
+                if actual != data_length {
+                    return Err(Error::DataLengthMismatch {
+                        expected: data_length,
+                        actual,
+                    });
+                }
             }
         }
 
@@ -79,6 +125,14 @@ impl PacketDecoder {
     pub fn disable_compression(&mut self) {
         self.threshold = None;
     }
+
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
 }
 
 impl Decoder for PacketDecoder {
@@ -108,3 +162,99 @@ impl Decoder for PacketDecoder {
         Ok(Some(raw_packet))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use flate2::{Compression, write::ZlibEncoder};
+
+    use super::*;
+
+    #[test]
+    fn test_frame_too_large() {
+        let mut src = BytesMut::new();
+        var_int::serialize(&i32::MAX, &mut src).unwrap();
+        assert!(matches!(
+            PacketDecoder::default().decode(&mut src),
+            Err(Error::FrameTooLarge { .. })
+        ));
+
+        let mut src = BytesMut::new();
+        var_int::serialize(&-1, &mut src).unwrap();
+        assert!(matches!(
+            PacketDecoder::default().decode(&mut src),
+            Err(Error::InvalidFrameLength(-1))
+        ));
+
+        let mut decoder = PacketDecoder::default();
+        decoder.set_limits(Limits {
+            max_frame_length: 16,
+            ..Limits::DEFAULT
+        });
+        let mut src = BytesMut::new();
+        var_int::serialize(&17, &mut src).unwrap();
+        assert!(matches!(
+            decoder.decode(&mut src),
+            Err(Error::FrameTooLarge {
+                length: 17,
+                max: 16
+            })
+        ));
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Frames `payload` in the compressed format.
+    fn compressed_frame(data_length: i32, payload: &[u8]) -> BytesMut {
+        let mut frame = BytesMut::new();
+        var_int::serialize(&data_length, &mut frame).unwrap();
+        frame.put_slice(payload);
+        let mut src = BytesMut::new();
+        var_int::serialize(&(frame.len() as i32), &mut src).unwrap();
+        src.put(frame);
+        src
+    }
+
+    fn compressed_decoder() -> PacketDecoder {
+        let mut decoder = PacketDecoder::default();
+        decoder.enable_compression(256);
+        decoder
+    }
+
+    #[test]
+    fn test_data_length_mismatch() {
+        let mut src = compressed_frame(300, &deflate(&[0u8; 4096]));
+        assert!(matches!(
+            compressed_decoder().decode(&mut src),
+            Err(Error::DataLengthMismatch {
+                expected: 300,
+                actual: 301
+            })
+        ));
+    }
+
+    #[test]
+    fn test_threshold_validation() {
+        let mut src = compressed_frame(0, &[0u8; 256]);
+        assert!(matches!(
+            compressed_decoder().decode(&mut src),
+            Err(Error::UncompressedAboveThreshold { length: 256, .. })
+        ));
+
+        let mut src = compressed_frame(16, &deflate(&[0u8; 16]));
+        assert!(matches!(
+            compressed_decoder().decode(&mut src),
+            Err(Error::CompressedBelowThreshold { length: 16, .. })
+        ));
+
+        let mut decoder = compressed_decoder();
+        decoder.set_lenient(true);
+        let mut src = compressed_frame(16, &deflate(&[0u8; 16]));
+        assert_eq!(decoder.decode(&mut src).unwrap().unwrap().id, 0);
+    }
+}
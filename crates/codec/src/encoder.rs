@@ -1,6 +1,6 @@
 use bytes::{Buf, BufMut, BytesMut};
 use flate2::{Compression, read::ZlibEncoder};
-use minecrust_protocol::{Serialize, datatype::var_int};
+use minecrust_protocol::{Limits, datatype::var_int};
 use std::io::copy;
 use tokio_util::codec::Encoder;
 
@@ -10,6 +10,7 @@ use crate::{Error, crypto::Cfb8Cipher, packet::RawPacket};
 pub struct PacketEncoder {
     cipher: Option<Cfb8Cipher>,
     threshold: Option<usize>,
+    limits: Limits,
 }
 
 impl PacketEncoder {
@@ -22,12 +23,19 @@ impl PacketEncoder {
     }
 
     fn deflate(&mut self, frame: &mut BytesMut) -> Result<(), Error> {
-        if let Some(threshold) = &mut self.threshold {
-            let packet_size = frame.remaining();
+        let packet_size = frame.remaining();
+        let max = self.limits.max_uncompressed_length;
+        if packet_size > max {
+            return Err(Error::PacketTooLarge {
+                length: packet_size,
+                max,
+            });
+        }
 
-            if packet_size >= *threshold {
+        if let Some(threshold) = self.threshold {
+            if packet_size >= threshold {
                 let inflated_bytes = frame.split();
-                var_int::serialize(&(packet_size as i32), frame);
+                var_int::serialize(&(packet_size as i32), frame)?;
                 let mut writer = frame.writer();
                 let mut encoder = ZlibEncoder::new(&*inflated_bytes, Compression::default());
                 copy(&mut encoder, &mut writer)?;
@@ -62,6 +70,10 @@ impl PacketEncoder {
     pub fn disable_compression(&mut self) {
         self.threshold = None;
     }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
 }
 
 impl Encoder<RawPacket> for PacketEncoder {
@@ -70,12 +82,19 @@ impl Encoder<RawPacket> for PacketEncoder {
     fn encode(&mut self, raw_packet: RawPacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
         tracing::trace!(?raw_packet, "encoding packet");
         let mut frame = BytesMut::new();
-        var_int::serialize(&raw_packet.id, &mut frame);
-        raw_packet.data.serialize(&mut frame);
+        var_int::serialize(&raw_packet.id, &mut frame)?;
+        frame.put_slice(&raw_packet.data);
 
         self.deflate(&mut frame)?;
+        let max = self.limits.max_frame_length;
+        if frame.remaining() > max {
+            return Err(Error::FrameTooLarge {
+                length: frame.remaining(),
+                max,
+            });
+        }
         let finished_frame = frame.split();
-        var_int::serialize(&(finished_frame.remaining() as i32), &mut frame);
+        var_int::serialize(&(finished_frame.remaining() as i32), &mut frame)?;
         frame.unsplit(finished_frame);
 
         self.encrypt(&mut frame);
@@ -84,3 +103,81 @@ impl Encoder<RawPacket> for PacketEncoder {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bytes::Bytes;
+    use tokio_util::codec::Decoder;
+
+    use super::*;
+    use crate::decoder::PacketDecoder;
+
+    fn packet(length: usize) -> RawPacket {
+        RawPacket {
+            id: 0,
+            data: Bytes::from(vec![0u8; length]),
+        }
+    }
+
+    #[test]
+    fn test_packet_too_large() {
+        let mut encoder = PacketEncoder::default();
+        encoder.set_limits(Limits {
+            max_uncompressed_length: 16,
+            ..Limits::DEFAULT
+        });
+        assert!(matches!(
+            encoder.encode(packet(16), &mut BytesMut::new()),
+            Err(Error::PacketTooLarge {
+                length: 17,
+                max: 16
+            })
+        ));
+    }
+
+    #[test]
+    fn test_frame_too_large() {
+        let mut encoder = PacketEncoder::default();
+        encoder.set_limits(Limits {
+            max_frame_length: 16,
+            ..Limits::DEFAULT
+        });
+        let mut dst = BytesMut::new();
+        encoder.encode(packet(15), &mut dst).unwrap();
+        assert!(matches!(
+            encoder.encode(packet(16), &mut dst),
+            Err(Error::FrameTooLarge {
+                length: 17,
+                max: 16
+            })
+        ));
+
+        // Compression does not lift the limit for frames that stay large.
+        encoder.enable_compression(256);
+        assert!(matches!(
+            encoder.encode(packet(16), &mut dst),
+            Err(Error::FrameTooLarge {
+                length: 18,
+                max: 16
+            })
+        ));
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let mut encoder = PacketEncoder::default();
+        encoder.enable_compression(256);
+        let mut decoder = PacketDecoder::default();
+        decoder.enable_compression(256);
+
+        let mut dst = BytesMut::new();
+        for length in [16, 4096] {
+            encoder.encode(packet(length), &mut dst).unwrap();
+        }
+        for length in [16, 4096] {
+            let decoded = decoder.decode(&mut dst).unwrap().unwrap();
+            assert_eq!(decoded.data.len(), length);
+        }
+        assert!(dst.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use minecrust_protocol::Limits;
 use thiserror::Error;
 use tokio_util::codec::{Decoder, Encoder};
 
@@ -20,6 +21,22 @@ pub enum Error {
     Decompress(#[from] flate2::DecompressError),
     #[error("packet deflation failed: {0}")]
     Deflate(&'static str),
+    #[error("invalid frame length {0}")]
+    InvalidFrameLength(i32),
+    #[error("frame of {length} bytes exceeds the maximum of {max}")]
+    FrameTooLarge { length: usize, max: usize },
+    #[error("packet of {length} bytes exceeds the maximum of {max} after decompression")]
+    PacketTooLarge { length: usize, max: usize },
+    #[error(
+        "uncompressed packet of {length} bytes reaches the compression threshold of {threshold}"
+    )]
+    UncompressedAboveThreshold { length: usize, threshold: usize },
+    #[error(
+        "compressed packet of {length} bytes is below the compression threshold of {threshold}"
+    )]
+    CompressedBelowThreshold { length: usize, threshold: usize },
+    #[error("packet declared {expected} bytes but decompressed to {actual}")]
+    DataLengthMismatch { expected: usize, actual: usize },
 }
 
 #[derive(Default)]
@@ -43,6 +60,19 @@ impl PacketCodec {
         self.decoder.disable_compression();
         self.encoder.disable_compression();
     }
+
+    /// Only logs received frames that violate the compressed frame format instead of failing,
+    /// for debugging clients that do not follow it. Hard limits are still enforced, and frames
+    /// exceeding them are never sent.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.decoder.set_lenient(lenient);
+    }
+
+    /// Replaces the frame and packet length limits, vanilla's by default.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.decoder.set_limits(limits);
+        self.encoder.set_limits(limits);
+    }
 }
 
 impl Decoder for PacketCodec {
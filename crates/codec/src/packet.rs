@@ -1,38 +1 @@
-use bytes::{Bytes, BytesMut};
-use minecrust_protocol::{Deserialize, Error, Serialize, datatype::var_int};
-
-#[derive(Debug, Clone)]
-pub struct RawPacket {
-    pub id: i32,
-    pub data: Bytes,
-}
-
-impl TryFrom<BytesMut> for RawPacket {
-    type Error = Error;
-
-    fn try_from(mut data: BytesMut) -> Result<Self, Self::Error> {
-        let id = var_int::deserialize(&mut data)?;
-
-        Ok(RawPacket {
-            id,
-            data: data.freeze(),
-        })
-    }
-}
-
-impl<S: Serialize> From<(i32, S)> for RawPacket {
-    fn from((id, data): (i32, S)) -> Self {
-        let mut buffer = BytesMut::new();
-        data.serialize(&mut buffer);
-        Self {
-            id,
-            data: buffer.freeze(),
-        }
-    }
-}
-
-impl RawPacket {
-    pub fn try_into<P: Deserialize>(mut self) -> Result<P, Error> {
-        P::deserialize(&mut self.data)
-    }
-}
+pub use minecrust_protocol::packet::RawPacket;
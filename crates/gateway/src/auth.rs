@@ -0,0 +1,225 @@
+//! Online-mode authentication against the session server.
+
+use std::{net::IpAddr, time::Duration};
+
+use md5::Md5;
+use minecrust_protocol::datatype::{GameProfile, GameProfileProperties};
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+use uuid::{Builder, Uuid};
+
+/// Session server of Mojang, used unless configured otherwise.
+pub const DEFAULT_SESSION_SERVER: &str = "https://sessionserver.mojang.com";
+
+#[derive(Debug, Error)]
+pub(crate) enum AuthError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("invalid session server response: {0}")]
+    InvalidResponse(&'static str),
+}
+
+/// The server hash as expected by the session server: a SHA-1 digest formatted as a signed,
+/// two's-complement hex number without leading zeros.
+pub(crate) fn server_hash(server_id: &str, shared_secret: &[u8], public_key: &[u8]) -> String {
+    let mut digest: [u8; 20] = Sha1::new()
+        .chain_update(server_id.as_bytes())
+        .chain_update(shared_secret)
+        .chain_update(public_key)
+        .finalize()
+        .into();
+
+    let negative = digest[0] & 0x80 != 0;
+    if negative {
+        let mut carry = true;
+        for byte in digest.iter_mut().rev() {
+            (*byte, carry) = (!*byte).overflowing_add(carry as u8);
+        }
+    }
+
+    let hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    let hex = hex.trim_start_matches('0');
+    if negative {
+        format!("-{hex}")
+    } else {
+        hex.to_string()
+    }
+}
+
+/// The version 3 UUID vanilla servers assign in offline mode, derived from the name alone.
+pub(crate) fn offline_uuid(username: &str) -> Uuid {
+    let digest = Md5::digest(format!("OfflinePlayer:{username}"));
+    Builder::from_md5_bytes(digest.into()).into_uuid()
+}
+
+/// Asks the session server whether `username` joined with `server_hash`. Returns `None` if the
+/// player did not authenticate, and an error if the server did not answer within `timeout`.
+pub(crate) async fn has_joined(
+    client: &reqwest::Client,
+    session_server: &str,
+    timeout: Duration,
+    username: &str,
+    server_hash: &str,
+    ip: Option<IpAddr>,
+) -> Result<Option<GameProfile>, AuthError> {
+    let mut query = vec![
+        ("username", username.to_string()),
+        ("serverId", server_hash.to_string()),
+    ];
+    if let Some(ip) = ip {
+        query.push(("ip", ip.to_string()));
+    }
+
+    let response = client
+        .get(format!(
+            "{}/session/minecraft/hasJoined",
+            session_server.trim_end_matches('/')
+        ))
+        .query(&query)
+        .timeout(timeout)
+        .send()
+        .await?
+        .error_for_status()?;
+    if response.status() == reqwest::StatusCode::NO_CONTENT {
+        return Ok(None);
+    }
+    let body = response.bytes().await?;
+    if body.is_empty() {
+        return Ok(None);
+    }
+
+    let profile: Value = serde_json::from_slice(&body)?;
+    let string = |value: &Value| {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or(AuthError::InvalidResponse("expected string"))
+    };
+
+    let properties = match profile.get("properties").and_then(Value::as_array) {
+        None => vec![],
+        Some(properties) => properties
+            .iter()
+            .map(|property| {
+                Ok(GameProfileProperties {
+                    name: string(&property["name"])?,
+                    value: string(&property["value"])?,
+                    signature: property.get("signature").map(string).transpose()?,
+                })
+            })
+            .collect::<Result<_, AuthError>>()?,
+    };
+
+    Ok(Some(GameProfile {
+        uuid: Uuid::parse_str(&string(&profile["id"])?)
+            .map_err(|_| AuthError::InvalidResponse("invalid profile id"))?,
+        username: string(&profile["name"])?,
+        properties,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    fn sha1_hash(name: &str) -> String {
+        server_hash(name, &[], &[])
+    }
+
+    #[test]
+    fn test_server_hash() {
+        assert_eq!(
+            sha1_hash("Notch"),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            sha1_hash("jeb_"),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        assert_eq!(
+            sha1_hash("simon"),
+            "88e16a1019277b15d58faf0541e11910eb756f6"
+        );
+    }
+
+    #[test]
+    fn test_offline_uuid() {
+        assert_eq!(
+            offline_uuid("Notch"),
+            Uuid::parse_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap()
+        );
+    }
+
+    /// Serves a single request with `response`, handing back the request line.
+    async fn stand_in(response: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let read = stream.read(&mut request).await.unwrap();
+            stream.write_all(response.as_bytes()).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..read]).into_owned();
+            request.lines().next().unwrap_or_default().to_string()
+        });
+        (format!("http://{addr}"), handle)
+    }
+
+    #[tokio::test]
+    async fn test_has_joined() {
+        let body = r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","properties":[{"name":"textures","value":"e30=","signature":"c2ln"}]}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let (url, request) = stand_in(response.leak()).await;
+
+        let profile = has_joined(
+            &reqwest::Client::new(),
+            &url,
+            Duration::from_secs(5),
+            "Notch",
+            "-1a2b",
+            Some("127.0.0.1".parse().unwrap()),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            request.await.unwrap(),
+            "GET /session/minecraft/hasJoined?username=Notch&serverId=-1a2b&ip=127.0.0.1 HTTP/1.1"
+        );
+        assert_eq!(profile.username, "Notch");
+        assert_eq!(
+            profile.uuid,
+            Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap()
+        );
+        assert_eq!(profile.properties[0].name, "textures");
+        assert_eq!(profile.properties[0].signature.as_deref(), Some("c2ln"));
+    }
+
+    #[tokio::test]
+    async fn test_has_not_joined() {
+        let (url, _) = stand_in("HTTP/1.1 204 No Content\r\nconnection: close\r\n\r\n").await;
+        let profile = has_joined(
+            &reqwest::Client::new(),
+            &url,
+            Duration::from_secs(5),
+            "Notch",
+            "1",
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(profile.is_none());
+    }
+}
@@ -0,0 +1,123 @@
+use std::{ops::RangeInclusive, path::Path, sync::Arc, time::Duration};
+
+use minecrust_protocol::{
+    Limits,
+    datatype::{Favicon, TextComponent},
+};
+use thiserror::Error;
+
+use crate::{ServerKey, auth::DEFAULT_SESSION_SERVER, dispatcher::IMPLEMENTED_PROTOCOLS};
+
+/// Disconnect reason sent to connected players on shutdown when none is configured, in tag markup.
+pub const DEFAULT_SHUTDOWN_MESSAGE: &str = "<lang:multiplayer.disconnect.server_shutdown>";
+
+/// Time a session server request may take before the login fails as if the server were down.
+pub const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Server list description used when none is configured, in tag markup.
+pub const DEFAULT_DESCRIPTION: &str = "<gradient:gold:yellow>Minecrust</gradient> <gray>gateway";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Protocol(#[from] minecrust_protocol::Error),
+    #[error("no dispatcher implements protocols {}-{}", .0.start(), .0.end())]
+    UnimplementedProtocols(RangeInclusive<i32>),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuthMode {
+    /// Players are encrypted and authenticated against the session server.
+    #[default]
+    Online,
+    /// Skips encryption and authentication, for development or behind a proxy.
+    Offline,
+}
+
+/// Gateway settings, swapped at runtime through an `ArcSwap`.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Keypair for new logins. Logins in progress keep the key they started with, so it can be
+    /// rotated by storing a new config.
+    pub key: Arc<ServerKey>,
+    pub description: TextComponent,
+    pub max_players: i32,
+    pub favicon: Option<Favicon>,
+    pub enforces_secure_chat: bool,
+    /// Protocol versions reported as compatible in the server list and accepted on login. Each
+    /// must be implemented by a dispatcher, see [`Config::validate`].
+    pub supported_protocols: Vec<RangeInclusive<i32>>,
+    pub auth_mode: AuthMode,
+    /// Base URL of the session server players are authenticated against.
+    pub session_server: String,
+    /// Time a session server request may take, connecting included, before the login fails.
+    pub session_timeout: Duration,
+    /// Rejects players whose session was created from another IP address.
+    pub prevent_proxy_connections: bool,
+    /// Accepts compressed frames that violate the threshold rules, for debugging clients.
+    pub lenient_framing: bool,
+    /// Bounds on untrusted input, taken by each connection when it is accepted.
+    pub limits: Limits,
+    /// Disconnect reason sent to every connected player when the gateway shuts down.
+    pub shutdown_message: TextComponent,
+}
+
+impl Config {
+    pub fn new(key: Arc<ServerKey>) -> Self {
+        Self {
+            key,
+            description: TextComponent::from_markup(DEFAULT_DESCRIPTION),
+            max_players: 20,
+            favicon: None,
+            enforces_secure_chat: false,
+            supported_protocols: vec![773..=773],
+            auth_mode: AuthMode::Online,
+            session_server: DEFAULT_SESSION_SERVER.to_string(),
+            session_timeout: DEFAULT_SESSION_TIMEOUT,
+            prevent_proxy_connections: false,
+            lenient_framing: false,
+            limits: Limits::DEFAULT,
+            shutdown_message: TextComponent::from_markup(DEFAULT_SHUTDOWN_MESSAGE),
+        }
+    }
+
+    /// Rejects supported protocols that the gateway cannot speak.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for range in &self.supported_protocols {
+            let implemented = IMPLEMENTED_PROTOCOLS.iter().any(|implemented| {
+                implemented.contains(range.start()) && implemented.contains(range.end())
+            });
+            if !implemented {
+                return Err(ConfigError::UnimplementedProtocols(range.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a 64x64 PNG server icon from disk.
+pub fn load_favicon(path: impl AsRef<Path>) -> Result<Favicon, ConfigError> {
+    Ok(Favicon::from_png(&std::fs::read(path)?)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_supported_protocols() {
+        let config = Config::new(Arc::new(ServerKey::generate().unwrap()));
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            supported_protocols: vec![773..=773, 767..=769],
+            ..config
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::UnimplementedProtocols(range)) if range == (767..=769)
+        ));
+    }
+}
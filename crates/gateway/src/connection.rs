@@ -1,11 +1,25 @@
+use std::{net::SocketAddr, sync::Arc};
+
 use futures::SinkExt;
 use minecrust_codec::{PacketCodec, packet::RawPacket};
+use minecrust_protocol::{
+    Limits,
+    datatype::TextComponent,
+    packet::{
+        legacy,
+        v773::client::{configuration, login::LoginDisconnect, play},
+    },
+};
 use thiserror::Error;
 use tokio::{net::TcpStream, task::JoinError};
 use tokio_stream::StreamExt;
 use tokio_util::{codec::Framed, sync::CancellationToken};
 
-use crate::dispatcher::{self, Dispatcher};
+use crate::{
+    auth::AuthError,
+    dispatcher::{self, Dispatcher, unversioned},
+    state::GatewayState,
+};
 
 #[derive(Debug, Error)]
 pub(crate) enum ConnectionError {
@@ -17,16 +31,60 @@ pub(crate) enum ConnectionError {
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Join(#[from] JoinError),
+    #[error(transparent)]
+    Login(#[from] LoginError),
     #[error("{0}")]
     Custom(&'static str),
 }
 
+/// Failures during login, each answered with a disconnect before the connection is closed.
+#[derive(Debug, Error)]
+pub(crate) enum LoginError {
+    #[error("encryption response before hello")]
+    KeyBeforeHello,
+    #[error("login acknowledged before login finished")]
+    AcknowledgedBeforeFinished,
+    #[error("failed to decrypt encryption response: {0}")]
+    Decrypt(#[from] rsa::Error),
+    #[error("verify token mismatch")]
+    VerifyTokenMismatch,
+    #[error("shared secret has invalid length {0}")]
+    SharedSecretLength(usize),
+    #[error("player did not authenticate")]
+    Unverified,
+    #[error("session server request failed: {0}")]
+    AuthServersDown(#[from] AuthError),
+}
+
+impl ConnectionError {
+    /// Disconnect reason shown to the player.
+    pub fn reason(&self) -> TextComponent {
+        match self {
+            Self::Login(err) => err.reason(),
+            _ => TextComponent::translatable("disconnect.packetError", vec![]),
+        }
+    }
+}
+
+impl LoginError {
+    /// Disconnect reason shown to the player.
+    pub fn reason(&self) -> TextComponent {
+        let key = match self {
+            Self::Unverified => "multiplayer.disconnect.unverified_username",
+            Self::AuthServersDown(_) => "multiplayer.disconnect.authservers_down",
+            _ => "disconnect.packetError",
+        };
+        TextComponent::translatable(key, vec![])
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum ProtocolState {
     Handshake,
     Status,
     Login,
     Configuration,
+    Play,
 }
 
 #[derive(Debug, Clone)]
@@ -36,21 +94,38 @@ pub(crate) enum Action {
     ProtocolState(ProtocolState),
     ProtocolVersion(u32),
     SendPacket(RawPacket),
+    /// Sends the reason in the disconnect packet of the current state and closes the connection.
+    Disconnect(Box<TextComponent>),
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Context {
-    protocol_state: ProtocolState,
-    protocol_version: u32,
+/// Connection data shared with every dispatcher.
+#[derive(Debug)]
+pub(crate) struct Session {
+    pub state: Arc<GatewayState>,
+    pub remote_addr: SocketAddr,
+    pub protocol_state: ProtocolState,
+    pub protocol_version: u32,
+    /// Limits applied to every packet decoded on this connection.
+    pub limits: Limits,
 }
 
-fn get_dispatcher(context: &Context) -> Result<Box<dyn Dispatcher + Send>, ConnectionError> {
+fn get_dispatcher(session: &Session) -> Result<Box<dyn Dispatcher + Send>, ConnectionError> {
     let dispatcher: Box<dyn Dispatcher + Send> =
-        match (context.protocol_state, context.protocol_version) {
-            (ProtocolState::Status, 773..) => Box::new(dispatcher::v773::StatusDispatcher),
-            (ProtocolState::Login, 773..) => Box::new(dispatcher::v773::LoginDispatcher::new()),
+        match (session.protocol_state, session.protocol_version as i32) {
+            (ProtocolState::Status, _) => Box::new(dispatcher::unversioned::StatusDispatcher),
+            (ProtocolState::Login, version) if dispatcher::v773::PROTOCOLS.contains(&version) => {
+                Box::new(dispatcher::v773::LoginDispatcher::new())
+            }
+            (ProtocolState::Configuration, version)
+                if dispatcher::v773::PROTOCOLS.contains(&version) =>
+            {
+                Box::new(dispatcher::v773::ConfigurationDispatcher::new())
+            }
+            (ProtocolState::Play, version) if dispatcher::v773::PROTOCOLS.contains(&version) => {
+                Box::new(dispatcher::v773::PlayDispatcher)
+            }
             (_, _) => {
-                tracing::error!(?context, "no dispatcher found");
+                tracing::error!(?session, "no dispatcher found");
                 return Err(ConnectionError::Custom("no dispatcher found"));
             }
         };
@@ -58,26 +133,104 @@ fn get_dispatcher(context: &Context) -> Result<Box<dyn Dispatcher + Send>, Conne
     Ok(dispatcher)
 }
 
+/// Disconnect reason for a login with a protocol outside the supported protocols, telling the
+/// player whether the client or the gateway is outdated.
+fn unsupported_login(session: &Session) -> Option<TextComponent> {
+    let ProtocolState::Login = session.protocol_state else {
+        return None;
+    };
+    let config = session.state.config.load();
+    let version = session.protocol_version as i32;
+    if config
+        .supported_protocols
+        .iter()
+        .any(|range| range.contains(&version))
+    {
+        return None;
+    }
+    let newest = config
+        .supported_protocols
+        .iter()
+        .map(|range| *range.end())
+        .max()
+        .unwrap_or_default();
+    let key = if version > newest {
+        "multiplayer.disconnect.outdated_server"
+    } else {
+        "multiplayer.disconnect.outdated_client"
+    };
+    let versions = unversioned::supported_versions_name(&config.supported_protocols);
+    Some(TextComponent::translatable(key, vec![versions.into()]))
+}
+
 pub(crate) async fn handle_connection(
     shutdown_signal: CancellationToken,
+    state: Arc<GatewayState>,
     stream: TcpStream,
 ) -> Result<(), ConnectionError> {
     tracing::trace!("handle connection started");
 
-    let mut stream = Framed::new(stream, PacketCodec::default());
-    let mut context = Context {
+    let mut first_byte = [0u8; 1];
+    let peeked = tokio::select! {
+        biased;
+        _ = shutdown_signal.cancelled() => return Ok(()),
+        peeked = stream.peek(&mut first_byte) => peeked?,
+    };
+    if peeked == 0 {
+        return Ok(());
+    }
+    if first_byte[0] == legacy::PING_ID {
+        return crate::legacy::handle_legacy_ping(stream, &state).await;
+    }
+
+    let remote_addr = stream.peer_addr()?;
+    let config = state.config.load();
+    let limits = config.limits;
+    let mut codec = PacketCodec::default();
+    codec.set_lenient(config.lenient_framing);
+    codec.set_limits(limits);
+    drop(config);
+    let mut stream = Framed::new(stream, codec);
+    let mut session = Session {
+        state,
+        remote_addr,
         protocol_state: ProtocolState::Handshake,
         protocol_version: 0,
+        limits,
     };
+
+    let result = run_session(&shutdown_signal, &mut stream, &mut session).await;
+    if let Err(err) = &result {
+        tracing::debug!(%err, "closing connection after error");
+        if let Err(err) = disconnect(&mut stream, &session, err.reason()).await {
+            tracing::trace!(%err, "failed to send disconnect");
+        }
+    }
+
+    tracing::trace!("connection closed");
+    result
+}
+
+async fn run_session(
+    shutdown_signal: &CancellationToken,
+    stream: &mut Framed<TcpStream, PacketCodec>,
+    session: &mut Session,
+) -> Result<(), ConnectionError> {
     let mut dispatcher: Box<dyn Dispatcher + Send> =
         Box::new(dispatcher::unversioned::HandshakeDispatcher);
+    // Holding the guard keeps the player counted as online until the connection ends.
+    let mut player_guard = None;
 
     while let Some(raw_packet) = tokio::select! {
         biased;
-        _ = shutdown_signal.cancelled() => return Ok(()),
+        _ = shutdown_signal.cancelled() => return shutdown(stream, session).await,
         next_item = stream.next() => next_item.transpose()?
     } {
-        let actions = dispatcher.dispatch(raw_packet)?;
+        let actions = tokio::select! {
+            biased;
+            _ = shutdown_signal.cancelled() => return shutdown(stream, session).await,
+            actions = dispatcher.dispatch(session, raw_packet) => actions?,
+        };
 
         tracing::trace!(?actions, "running action");
         let mut context_changed = false;
@@ -90,23 +243,67 @@ pub(crate) async fn handle_connection(
                     stream.codec_mut().enable_compression(threshold);
                 }
                 Action::ProtocolState(new_protocol_state) => {
-                    context.protocol_state = new_protocol_state;
+                    if let ProtocolState::Play = new_protocol_state {
+                        player_guard.get_or_insert_with(|| session.state.join());
+                    }
+                    session.protocol_state = new_protocol_state;
                     context_changed = true;
                 }
                 Action::ProtocolVersion(new_protocol_version) => {
-                    context.protocol_version = new_protocol_version;
+                    session.protocol_version = new_protocol_version;
                     context_changed = true;
                 }
                 Action::SendPacket(packet) => {
                     stream.send(packet).await?;
                 }
+                Action::Disconnect(reason) => {
+                    return disconnect(stream, session, *reason).await;
+                }
             }
         }
         if context_changed {
-            dispatcher = get_dispatcher(&context)?;
+            if let Some(reason) = unsupported_login(session) {
+                tracing::debug!(
+                    protocol_version = session.protocol_version,
+                    "rejecting login of unsupported version"
+                );
+                return disconnect(stream, session, reason).await;
+            }
+            dispatcher = get_dispatcher(session)?;
         }
     }
 
-    tracing::trace!("connection closed");
+    Ok(())
+}
+
+/// Notifies the player that the gateway is shutting down and closes the connection.
+async fn shutdown(
+    stream: &mut Framed<TcpStream, PacketCodec>,
+    session: &Session,
+) -> Result<(), ConnectionError> {
+    tracing::trace!("disconnecting because of shutdown");
+    let reason = session.state.config.load().shutdown_message.clone();
+    disconnect(stream, session, reason).await
+}
+
+/// Sends `reason` if the current state has a disconnect packet, then shuts down the write half.
+async fn disconnect(
+    stream: &mut Framed<TcpStream, PacketCodec>,
+    session: &Session,
+    reason: TextComponent,
+) -> Result<(), ConnectionError> {
+    let packet: Option<RawPacket> = match session.protocol_state {
+        ProtocolState::Handshake | ProtocolState::Status => None,
+        ProtocolState::Login => Some(RawPacket::encode(&LoginDisconnect(reason))?),
+        ProtocolState::Configuration => {
+            Some(RawPacket::encode(&configuration::Disconnect(reason))?)
+        }
+        ProtocolState::Play => Some(RawPacket::encode(&play::Disconnect(reason))?),
+    };
+    if let Some(packet) = packet {
+        tracing::trace!(state = ?session.protocol_state, "sending disconnect");
+        stream.send(packet).await?;
+    }
+    stream.close().await?;
     Ok(())
 }
@@ -1,9 +1,21 @@
-use crate::connection::{Action, ConnectionError};
+use std::ops::RangeInclusive;
+
+use async_trait::async_trait;
 use minecrust_codec::packet::RawPacket;
 
+use crate::connection::{Action, ConnectionError, Session};
+
 pub(crate) mod unversioned;
 pub(crate) mod v773;
 
+/// Protocol versions that have login, configuration and play dispatchers.
+pub(crate) const IMPLEMENTED_PROTOCOLS: [RangeInclusive<i32>; 1] = [v773::PROTOCOLS];
+
+#[async_trait]
 pub(crate) trait Dispatcher {
-    fn dispatch(&mut self, raw_packet: RawPacket) -> Result<Vec<Action>, ConnectionError>;
+    async fn dispatch(
+        &mut self,
+        session: &mut Session,
+        raw_packet: RawPacket,
+    ) -> Result<Vec<Action>, ConnectionError>;
 }
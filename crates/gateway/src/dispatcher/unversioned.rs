@@ -1,27 +1,151 @@
+use std::ops::RangeInclusive;
+
+use async_trait::async_trait;
 use minecrust_codec::packet::RawPacket;
-use minecrust_protocol::{datatype::Intent, packet::unversioned::server::Intention};
+use minecrust_protocol::{
+    datatype::{Intent, ServerStatus, StatusPlayers, StatusVersion, TextComponent},
+    packet::unversioned::{
+        client::{PongResponse, StatusResponse},
+        server::{Intention, PingRequest, ServerboundHandshake, ServerboundStatus},
+    },
+    version,
+};
 
 use crate::{
-    connection::{Action, ConnectionError, ProtocolState},
+    connection::{Action, ConnectionError, ProtocolState, Session},
     dispatcher::Dispatcher,
 };
 
 pub(crate) struct HandshakeDispatcher;
 
+#[async_trait]
 impl Dispatcher for HandshakeDispatcher {
-    fn dispatch(&mut self, raw_packet: RawPacket) -> Result<Vec<Action>, ConnectionError> {
+    async fn dispatch(
+        &mut self,
+        session: &mut Session,
+        raw_packet: RawPacket,
+    ) -> Result<Vec<Action>, ConnectionError> {
         let mut state_changes = vec![];
 
-        let handshake: Intention = raw_packet.try_into()?;
+        let handshake: Intention = match ServerboundHandshake::decode(raw_packet, &session.limits)?
+        {
+            ServerboundHandshake::Intention(intention) => intention,
+            ServerboundHandshake::Unknown(raw_packet) => {
+                tracing::debug!(id = raw_packet.id, "unknown handshake packet");
+                return Err(ConnectionError::Custom(
+                    "unexpected packet during handshake",
+                ));
+            }
+        };
         tracing::trace!(?handshake, "performing handshake");
 
         state_changes.push(Action::ProtocolVersion(handshake.protocol_version as u32));
-        state_changes.push(match handshake.intent {
-            Intent::Login => Action::ProtocolState(ProtocolState::Login),
-            Intent::Status => Action::ProtocolState(ProtocolState::Status),
-            Intent::Transfer => unimplemented!("transfer intent is not implemented"),
-        });
+        match handshake.intent {
+            Intent::Login => state_changes.push(Action::ProtocolState(ProtocolState::Login)),
+            Intent::Status => state_changes.push(Action::ProtocolState(ProtocolState::Status)),
+            // Transfers log in like any other client, so they are refused with a login disconnect.
+            Intent::Transfer => {
+                tracing::debug!("rejecting transfer");
+                state_changes.push(Action::ProtocolState(ProtocolState::Login));
+                state_changes.push(Action::Disconnect(Box::new(TextComponent::translatable(
+                    "multiplayer.disconnect.transfers_disabled",
+                    vec![],
+                ))));
+            }
+        }
 
         Ok(state_changes)
     }
 }
+
+/// Answers status requests of every client, whatever protocol it speaks.
+pub(crate) struct StatusDispatcher;
+
+#[async_trait]
+impl Dispatcher for StatusDispatcher {
+    async fn dispatch(
+        &mut self,
+        session: &mut Session,
+        raw_packet: RawPacket,
+    ) -> Result<Vec<Action>, ConnectionError> {
+        let mut actions = vec![];
+        match ServerboundStatus::decode(raw_packet, &session.limits)? {
+            ServerboundStatus::StatusRequest(_) => {
+                let config = session.state.config.load();
+                let protocol = session.protocol_version as i32;
+                actions.push(Action::SendPacket(RawPacket::encode(&StatusResponse {
+                    status: ServerStatus {
+                        version: status_version(protocol, &config.supported_protocols),
+                        players: Some(StatusPlayers {
+                            max: config.max_players,
+                            online: session.state.online_players(),
+                            sample: vec![],
+                        }),
+                        description: config.description.clone(),
+                        favicon: config.favicon.clone(),
+                        enforces_secure_chat: config.enforces_secure_chat,
+                    },
+                    protocol,
+                })?));
+            }
+            ServerboundStatus::PingRequest(PingRequest(payload)) => {
+                actions.push(Action::SendPacket(RawPacket::encode(&PongResponse(
+                    payload,
+                ))?));
+            }
+            ServerboundStatus::Unknown(raw_packet) => {
+                tracing::debug!(id = raw_packet.id, "unknown status packet");
+            }
+        }
+
+        Ok(actions)
+    }
+}
+
+/// Echoes the client's protocol if it is supported. Otherwise reports the newest supported
+/// protocol, named after all supported releases, so the client shows it as incompatible.
+fn status_version(protocol: i32, supported: &[RangeInclusive<i32>]) -> StatusVersion {
+    if supported.iter().any(|range| range.contains(&protocol)) {
+        let name = match version::release(protocol) {
+            Some(release) => release.name(),
+            None => protocol.to_string(),
+        };
+        return StatusVersion { name, protocol };
+    }
+
+    StatusVersion {
+        name: supported_versions_name(supported),
+        protocol: supported
+            .iter()
+            .map(|range| *range.end())
+            .max()
+            .unwrap_or_default(),
+    }
+}
+
+/// Names all releases in the supported protocol ranges, e.g. `1.21-1.21.4, 1.21.9-1.21.10`.
+pub(crate) fn supported_versions_name(supported: &[RangeInclusive<i32>]) -> String {
+    supported
+        .iter()
+        .filter_map(version::range_name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_status_version() {
+        let supported = [767..=769, 773..=773];
+
+        let version = status_version(768, &supported);
+        assert_eq!(version.protocol, 768);
+        assert_eq!(version.name, "1.21.2-1.21.3");
+
+        let version = status_version(47, &supported);
+        assert_eq!(version.protocol, 773);
+        assert_eq!(version.name, "1.21-1.21.4, 1.21.9-1.21.10");
+    }
+}
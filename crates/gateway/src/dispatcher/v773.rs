@@ -1,159 +1,364 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use async_trait::async_trait;
+use bytes::BytesMut;
 use minecrust_codec::packet::RawPacket;
 use minecrust_protocol::{
-    datatype::{GameProfile, TextComponent},
+    Serialize,
+    datatype::{GameProfile, KnownPack},
     packet::v773::{
         client::{
             self,
-            status::{PongResponse, StatusResponse},
+            configuration::{
+                CustomPayload, FinishConfiguration, KeepAlive, RegistryData, RegistryEntry,
+                RegistryTags, SelectKnownPacks, Tag, UpdateEnabledFeatures, UpdateTags,
+            },
+        },
+        server::{
+            configuration::{self, ServerboundConfiguration},
+            login::{Hello, Key, ServerboundLogin},
         },
-        server::{self, status::PingRequest},
     },
 };
 use rand::Rng;
-use rsa::{Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey, pkcs8::EncodePublicKey};
-use uuid::Uuid;
 
 use crate::{
-    connection::{Action, ConnectionError, ProtocolState},
+    auth,
+    config::AuthMode,
+    connection::{Action, ConnectionError, LoginError, ProtocolState, Session},
     dispatcher::Dispatcher,
+    key::ServerKey,
 };
 
-pub(crate) struct StatusDispatcher;
+mod registries;
 
-impl Dispatcher for StatusDispatcher {
-    fn dispatch(&mut self, raw_packet: RawPacket) -> Result<Vec<Action>, ConnectionError> {
-        let mut actions = vec![];
-        match raw_packet.id {
-            // Status Request
-            0x00 => {
-                actions.push(Action::SendPacket(
-                    (
-                        0x00,
-                        StatusResponse(format!(
-                            // 773
-                            r#"{{ "version": {{ "name": "Maintenance", "protocol": 0 }}, "description": {{ "text": "{}" }}, "enforcesSecureChat": false }}"#,
-                            //SERVER_STATE.description.load()
-                            "Todo: Reimplement description"
-                        )),
-                    )
-                        .into(),
-                ));
-            }
-            // Ping Request
-            0x01 => {
-                let ping_request: PingRequest = raw_packet.try_into()?;
-                actions.push(Action::SendPacket(
-                    (0x01, PongResponse(ping_request.0)).into(),
-                ));
-            }
-            _ => {}
-        }
-
-        Ok(actions)
-    }
-}
+/// Protocol versions spoken by the dispatchers of this module.
+pub(crate) const PROTOCOLS: RangeInclusive<i32> = 773..=773;
 
 pub(crate) struct LoginDispatcher {
     verification_token: [u8; 32],
-    private_key: RsaPrivateKey,
-    public_key: Vec<u8>,
+    /// Key the encryption request was sent with, kept in case the key is rotated meanwhile.
+    key: Option<Arc<ServerKey>>,
     username: Option<String>,
-    uuid: Option<Uuid>,
+    finished: bool,
 }
 
 impl LoginDispatcher {
     pub fn new() -> Self {
-        let rng = &mut rand::thread_rng();
         let mut verification_token = [0u8; 32];
-        rng.fill(&mut verification_token);
-        let private_key = RsaPrivateKey::new(rng, 1024).unwrap();
-        let public_key = RsaPublicKey::from(&private_key)
-            .to_public_key_der()
-            .unwrap()
-            .to_vec();
+        rand::thread_rng().fill(&mut verification_token);
 
         Self {
             verification_token,
-            private_key,
-            public_key,
+            key: None,
             username: None,
-            uuid: None,
+            finished: false,
+        }
+    }
+
+    /// Enables compression and completes the login with `profile`.
+    fn finish(&mut self, profile: GameProfile) -> Result<[Action; 3], ConnectionError> {
+        self.finished = true;
+        Ok([
+            Action::SendPacket(RawPacket::encode(&client::login::LoginCompression(256))?),
+            Action::EnableCompression(256),
+            Action::SendPacket(RawPacket::encode(&client::login::LoginFinished(profile))?),
+        ])
+    }
+
+    /// Verifies the session of the player.
+    async fn authenticate(
+        &self,
+        session: &Session,
+        key: &ServerKey,
+        shared_secret: &[u8],
+    ) -> Result<GameProfile, LoginError> {
+        let config = session.state.config.load_full();
+        let username = self.username.as_deref().unwrap_or_default();
+        let server_hash = auth::server_hash("", shared_secret, key.public_key_der());
+        let ip = config
+            .prevent_proxy_connections
+            .then(|| session.remote_addr.ip());
+
+        let result = auth::has_joined(
+            &session.state.http,
+            &config.session_server,
+            config.session_timeout,
+            username,
+            &server_hash,
+            ip,
+        )
+        .await;
+        match result {
+            Ok(Some(profile)) if profile.username.eq_ignore_ascii_case(username) => Ok(profile),
+            Ok(_) => Err(LoginError::Unverified),
+            Err(err) => {
+                tracing::warn!(?err, username, "session server request failed");
+                Err(err.into())
+            }
         }
     }
 }
 
+#[async_trait]
 impl Dispatcher for LoginDispatcher {
-    fn dispatch(&mut self, raw_packet: RawPacket) -> Result<Vec<Action>, ConnectionError> {
+    async fn dispatch(
+        &mut self,
+        session: &mut Session,
+        raw_packet: RawPacket,
+    ) -> Result<Vec<Action>, ConnectionError> {
         let mut actions = vec![];
-        match raw_packet.id {
-            0x00 => {
-                let server::login::Hello { name, player_uuid } = raw_packet.try_into()?;
+        match ServerboundLogin::decode(raw_packet, &session.limits)? {
+            ServerboundLogin::Hello(Hello { name, player_uuid }) => {
                 tracing::trace!(name, ?player_uuid, "hello");
+                let config = session.state.config.load();
+                if let AuthMode::Offline = config.auth_mode {
+                    let profile = GameProfile {
+                        uuid: auth::offline_uuid(&name),
+                        username: name,
+                        properties: vec![],
+                    };
+                    actions.extend(self.finish(profile)?);
+                    return Ok(actions);
+                }
                 self.username = Some(name);
-                self.uuid = Some(player_uuid);
-
-                actions.push(Action::SendPacket(
-                    (
-                        0x01,
-                        client::login::Hello {
-                            server_id: String::new(),
-                            public_key: self.public_key.clone(),
-                            should_authenticate: true,
-                            verify_token: self.verification_token,
-                        },
-                    )
-                        .into(),
-                ));
+                self.key = Some(config.key.clone());
+
+                actions.push(Action::SendPacket(RawPacket::encode(
+                    &client::login::Hello {
+                        server_id: String::new(),
+                        public_key: config.key.public_key_der().to_vec(),
+                        should_authenticate: true,
+                        verify_token: self.verification_token,
+                    },
+                )?));
             }
-            0x01 => {
-                let server::login::Key {
-                    shared_secret,
-                    verify_token,
-                } = raw_packet.try_into()?;
-                let verification_token = self
-                    .private_key
-                    .decrypt(Pkcs1v15Encrypt, &verify_token)
-                    .unwrap();
+            ServerboundLogin::Key(Key {
+                shared_secret,
+                verify_token,
+            }) => {
+                // Taken so the handshake cannot be replayed.
+                let Some(key) = self.key.take() else {
+                    return Err(LoginError::KeyBeforeHello.into());
+                };
+                let verification_token = key.decrypt(&verify_token).map_err(LoginError::from)?;
                 if verification_token != self.verification_token {
-                    actions.push(Action::SendPacket(
-                        (
-                            0x00,
-                            client::login::LoginDisconnect(TextComponent(
-                                r#"{"type":"text","text":"Unsecure connection."}"#.to_string(),
-                            )),
-                        )
-                            .into(),
-                    ));
-                    // Actions::Disconnect
+                    return Err(LoginError::VerifyTokenMismatch.into());
+                }
+                let shared_secret = key.decrypt(&shared_secret).map_err(LoginError::from)?;
+                let shared_secret: [u8; 16] = shared_secret
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| LoginError::SharedSecretLength(shared_secret.len()))?;
+
+                // The client encrypts everything after its response, including a disconnect.
+                actions.push(Action::EnableEncryption(shared_secret));
+                match self.authenticate(session, &key, &shared_secret).await {
+                    Ok(profile) => actions.extend(self.finish(profile)?),
+                    Err(err) => {
+                        tracing::debug!(%err, "login failed");
+                        actions.push(Action::Disconnect(Box::new(err.reason())));
+                    }
                 }
-                let shared_secret = self
-                    .private_key
-                    .decrypt(Pkcs1v15Encrypt, &shared_secret)
-                    .unwrap();
-                actions.push(Action::EnableEncryption(
-                    shared_secret.as_slice().try_into().unwrap(),
-                ));
-                actions.push(Action::SendPacket(
-                    (0x03, client::login::LoginCompression(256)).into(),
-                ));
-                actions.push(Action::EnableCompression(256));
-                actions.push(Action::SendPacket(
-                    (
-                        0x02,
-                        client::login::LoginFinished(GameProfile {
-                            username: self.username.clone().unwrap(),
-                            uuid: self.uuid.unwrap(),
-                            properties: vec![],
-                        }),
-                    )
-                        .into(),
-                ));
             }
-            0x03 => {
+            ServerboundLogin::LoginAcknowledged(_) => {
+                if !self.finished {
+                    return Err(LoginError::AcknowledgedBeforeFinished.into());
+                }
                 actions.push(Action::ProtocolState(ProtocolState::Configuration));
             }
-            _ => {}
+            ServerboundLogin::CustomQueryAnswer(answer) => {
+                tracing::debug!(?answer, "ignoring unrequested custom query answer");
+            }
+            ServerboundLogin::CookieResponse(response) => {
+                tracing::debug!(?response, "ignoring unrequested cookie response");
+            }
+            ServerboundLogin::Unknown(raw_packet) => {
+                tracing::debug!(id = raw_packet.id, "unknown login packet");
+            }
+        }
+        Ok(actions)
+    }
+}
+
+pub(crate) struct ConfigurationDispatcher {
+    known_packs_sent: bool,
+    keep_alive_id: Option<i64>,
+}
+
+impl ConfigurationDispatcher {
+    pub fn new() -> Self {
+        Self {
+            known_packs_sent: false,
+            keep_alive_id: None,
+        }
+    }
+
+    fn core_packs() -> Vec<KnownPack> {
+        registries::CORE_PACK_VERSIONS
+            .iter()
+            .map(|version| KnownPack {
+                namespace: registries::CORE_PACK_NAMESPACE.to_string(),
+                id: registries::CORE_PACK_ID.to_string(),
+                version: version.to_string(),
+            })
+            .collect()
+    }
+
+    fn registry_data() -> impl Iterator<Item = RegistryData> {
+        registries::REGISTRIES
+            .iter()
+            .map(|(registry_id, entries)| RegistryData {
+                registry_id: registry_id.to_string(),
+                entries: entries
+                    .iter()
+                    .map(|entry| RegistryEntry {
+                        entry_id: format!("minecraft:{entry}"),
+                        data: None,
+                    })
+                    .collect(),
+            })
+    }
+
+    fn tags() -> UpdateTags {
+        let mut registry_tags: Vec<RegistryTags> = vec![];
+        for (registry, name, entries) in registries::TAGS {
+            let (_, registry_entries) = registries::REGISTRIES
+                .iter()
+                .find(|(id, _)| id == registry)
+                .expect("tagged registry must be synchronized");
+            let tag = Tag {
+                name: name.to_string(),
+                entries: entries
+                    .iter()
+                    .map(|entry| {
+                        registry_entries
+                            .iter()
+                            .position(|candidate| candidate == entry)
+                            .expect("tagged entry must be registered")
+                            as i32
+                    })
+                    .collect(),
+            };
+
+            match registry_tags
+                .iter_mut()
+                .find(|tags| tags.registry == *registry)
+            {
+                Some(tags) => tags.tags.push(tag),
+                None => registry_tags.push(RegistryTags {
+                    registry: registry.to_string(),
+                    tags: vec![tag],
+                }),
+            }
+        }
+        UpdateTags(registry_tags)
+    }
+}
+
+#[async_trait]
+impl Dispatcher for ConfigurationDispatcher {
+    async fn dispatch(
+        &mut self,
+        session: &mut Session,
+        raw_packet: RawPacket,
+    ) -> Result<Vec<Action>, ConnectionError> {
+        let mut actions = vec![];
+        match ServerboundConfiguration::decode(raw_packet, &session.limits)? {
+            ServerboundConfiguration::ClientInformation(information) => {
+                tracing::trace!(?information, "client information");
+                // The client resends its information whenever its settings change.
+                if self.known_packs_sent {
+                    return Ok(actions);
+                }
+                self.known_packs_sent = true;
+
+                let mut brand = BytesMut::new();
+                String::from("Minecrust").serialize(&mut brand)?;
+                actions.push(Action::SendPacket(RawPacket::encode(&CustomPayload {
+                    channel: String::from("minecraft:brand"),
+                    data: brand.freeze(),
+                })?));
+                actions.push(Action::SendPacket(RawPacket::encode(
+                    &UpdateEnabledFeatures(vec![String::from("minecraft:vanilla")]),
+                )?));
+                actions.push(Action::SendPacket(RawPacket::encode(&SelectKnownPacks(
+                    Self::core_packs(),
+                ))?));
+
+                let keep_alive_id = rand::thread_rng().r#gen();
+                self.keep_alive_id = Some(keep_alive_id);
+                actions.push(Action::SendPacket(RawPacket::encode(&KeepAlive(
+                    keep_alive_id,
+                ))?));
+            }
+            ServerboundConfiguration::SelectKnownPacks(configuration::SelectKnownPacks(packs)) => {
+                tracing::trace!(?packs, "client known packs");
+                let core_packs = Self::core_packs();
+                if !packs.iter().any(|pack| core_packs.contains(pack)) {
+                    return Err(ConnectionError::Custom(
+                        "client does not know the vanilla core pack",
+                    ));
+                }
+
+                for registry_data in Self::registry_data() {
+                    actions.push(Action::SendPacket(RawPacket::encode(&registry_data)?));
+                }
+                actions.push(Action::SendPacket(RawPacket::encode(&Self::tags())?));
+                actions.push(Action::SendPacket(RawPacket::encode(&FinishConfiguration)?));
+            }
+            ServerboundConfiguration::KeepAlive(configuration::KeepAlive(id)) => {
+                if self.keep_alive_id.take() != Some(id) {
+                    return Err(ConnectionError::Custom("unexpected keep alive"));
+                }
+            }
+            ServerboundConfiguration::FinishConfiguration(_) => {
+                actions.push(Action::ProtocolState(ProtocolState::Play));
+            }
+            ServerboundConfiguration::CustomPayload(payload) => {
+                tracing::trace!(channel = payload.channel, "custom payload");
+            }
+            ServerboundConfiguration::Pong(_)
+            | ServerboundConfiguration::CookieResponse(_)
+            | ServerboundConfiguration::ResourcePack(_)
+            | ServerboundConfiguration::CustomClickAction(_)
+            | ServerboundConfiguration::AcceptCodeOfConduct(_) => {
+                tracing::trace!("ignoring configuration packet");
+            }
+            ServerboundConfiguration::Unknown(raw_packet) => {
+                tracing::debug!(id = raw_packet.id, "unknown configuration packet");
+            }
         }
         Ok(actions)
     }
 }
+
+/// Placeholder until the play state is handed off to a backend, discards everything it receives.
+pub(crate) struct PlayDispatcher;
+
+#[async_trait]
+impl Dispatcher for PlayDispatcher {
+    async fn dispatch(
+        &mut self,
+        _session: &mut Session,
+        raw_packet: RawPacket,
+    ) -> Result<Vec<Action>, ConnectionError> {
+        tracing::trace!(id = raw_packet.id, "discarding play packet");
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tags_resolve_to_registered_entries() {
+        let UpdateTags(registry_tags) = ConfigurationDispatcher::tags();
+        let enchantments = registry_tags
+            .iter()
+            .find(|tags| tags.registry == "minecraft:enchantment")
+            .expect("enchantment tags are sent");
+        assert_eq!(enchantments.tags[0].entries, vec![2, 40]);
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fmt, path::Path};
+
+use rsa::{
+    Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey,
+    pkcs1::DecodeRsaPrivateKey,
+    pkcs8::{DecodePrivateKey, EncodePublicKey},
+};
+use thiserror::Error;
+
+/// Key size used by vanilla servers.
+const KEY_BITS: usize = 1024;
+
+#[derive(Debug, Error)]
+pub enum KeyError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Rsa(#[from] rsa::Error),
+    #[error(transparent)]
+    Pkcs1(#[from] rsa::pkcs1::Error),
+    #[error(transparent)]
+    Pkcs8(#[from] rsa::pkcs8::Error),
+    #[error(transparent)]
+    Spki(#[from] rsa::pkcs8::spki::Error),
+}
+
+/// RSA keypair of the encryption handshake, shared by all logins of a gateway.
+pub struct ServerKey {
+    private_key: RsaPrivateKey,
+    /// Public key in the DER encoding sent to clients.
+    public_key: Vec<u8>,
+}
+
+impl ServerKey {
+    pub fn generate() -> Result<Self, KeyError> {
+        Self::from_private_key(RsaPrivateKey::new(&mut rand::thread_rng(), KEY_BITS)?)
+    }
+
+    /// Reads a PKCS#8 or PKCS#1 private key, PEM or DER encoded.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeyError> {
+        let bytes = std::fs::read(path)?;
+        match std::str::from_utf8(&bytes) {
+            Ok(pem) if pem.trim_start().starts_with("-----BEGIN") => Self::from_pem(pem),
+            _ => Self::from_der(&bytes),
+        }
+    }
+
+    pub fn from_pem(pem: &str) -> Result<Self, KeyError> {
+        let private_key = match RsaPrivateKey::from_pkcs8_pem(pem) {
+            Ok(private_key) => private_key,
+            Err(_) => RsaPrivateKey::from_pkcs1_pem(pem)?,
+        };
+        Self::from_private_key(private_key)
+    }
+
+    pub fn from_der(der: &[u8]) -> Result<Self, KeyError> {
+        let private_key = match RsaPrivateKey::from_pkcs8_der(der) {
+            Ok(private_key) => private_key,
+            Err(_) => RsaPrivateKey::from_pkcs1_der(der)?,
+        };
+        Self::from_private_key(private_key)
+    }
+
+    fn from_private_key(private_key: RsaPrivateKey) -> Result<Self, KeyError> {
+        let public_key = RsaPublicKey::from(&private_key)
+            .to_public_key_der()?
+            .to_vec();
+        Ok(Self {
+            private_key,
+            public_key,
+        })
+    }
+
+    pub fn public_key_der(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    pub(crate) fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, rsa::Error> {
+        self.private_key.decrypt(Pkcs1v15Encrypt, ciphertext)
+    }
+}
+
+impl fmt::Debug for ServerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerKey")
+            .field("public_key_len", &self.public_key.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rsa::pkcs8::{EncodePrivateKey, LineEnding};
+
+    use super::*;
+
+    #[test]
+    fn test_load_encodings() {
+        let key = ServerKey::generate().unwrap();
+        let pem = key.private_key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        let der = key.private_key.to_pkcs8_der().unwrap();
+
+        assert_eq!(
+            ServerKey::from_pem(&pem).unwrap().public_key_der(),
+            key.public_key_der()
+        );
+        assert_eq!(
+            ServerKey::from_der(der.as_bytes())
+                .unwrap()
+                .public_key_der(),
+            key.public_key_der()
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use bytes::BytesMut;
+use minecrust_protocol::{
+    datatype::text_component::legacy::SECTION,
+    packet::legacy::{INCOMPATIBLE_PROTOCOL, LegacyPing, LegacyPingResponse},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+
+use crate::{connection::ConnectionError, dispatcher::unversioned, state::GatewayState};
+
+/// How long to wait for further bytes, as older clients send nothing after their ping.
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+/// Largest legacy ping accepted, well above what a 1.6 client sends.
+const MAX_PING_LENGTH: usize = 1024;
+
+/// Answers a pre-netty server list ping and closes the connection.
+pub(crate) async fn handle_legacy_ping(
+    mut stream: TcpStream,
+    state: &GatewayState,
+) -> Result<(), ConnectionError> {
+    let mut buf = BytesMut::with_capacity(64);
+    let ping = loop {
+        let complete = match timeout(READ_TIMEOUT, stream.read_buf(&mut buf)).await {
+            Ok(read) => read? == 0 || buf.len() >= MAX_PING_LENGTH,
+            Err(_) => true,
+        };
+        if let Some(ping) = LegacyPing::decode(&buf, complete)? {
+            break ping;
+        }
+    };
+    tracing::trace!(?ping, "answering legacy ping");
+
+    let config = state.config.load();
+    let response = LegacyPingResponse {
+        protocol_version: INCOMPATIBLE_PROTOCOL,
+        version_name: unversioned::supported_versions_name(&config.supported_protocols),
+        motd: config
+            .description
+            .clone()
+            .with_named_colors()
+            .to_legacy(SECTION),
+        online_players: state.online_players(),
+        max_players: config.max_players,
+    };
+    stream.write_all(&response.encode(&ping)).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
@@ -1,19 +1,44 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
+use arc_swap::ArcSwap;
 use tokio::net::TcpListener;
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
 
+mod auth;
+pub mod config;
 mod connection;
 mod dispatcher;
+mod key;
+mod legacy;
+mod state;
+
+pub use auth::DEFAULT_SESSION_SERVER;
+pub use config::Config;
+pub use key::{KeyError, ServerKey};
+
+use crate::state::GatewayState;
 
 pub async fn run(
     cancellation_token: CancellationToken,
     tracker: TaskTracker,
     addr: SocketAddr,
+    config: Arc<ArcSwap<Config>>,
 ) -> Result<(), tokio::io::Error> {
     let listener = TcpListener::bind(addr).await?;
     tracing::debug!(?addr, "listener created");
 
+    serve(cancellation_token, tracker, listener, config).await
+}
+
+/// Accepts connections on an already bound listener until cancelled.
+pub async fn serve(
+    cancellation_token: CancellationToken,
+    tracker: TaskTracker,
+    listener: TcpListener,
+    config: Arc<ArcSwap<Config>>,
+) -> Result<(), tokio::io::Error> {
+    let state = Arc::new(GatewayState::new(config));
+
     loop {
         tracing::trace!("waiting for connection");
         tokio::select! {
@@ -29,7 +54,7 @@ pub async fn run(
                 let cancellation_token = cancellation_token.clone();
                 tracing::trace!(?remote_addr, "connection accepted");
 
-                tracker.spawn(connection::handle_connection(cancellation_token, stream));
+                tracker.spawn(connection::handle_connection(cancellation_token, state.clone(), stream));
             }
         }
     }
@@ -0,0 +1,45 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicI32, Ordering},
+};
+
+use arc_swap::ArcSwap;
+
+use crate::Config;
+
+/// State shared by every connection of a gateway.
+#[derive(Debug)]
+pub(crate) struct GatewayState {
+    pub config: Arc<ArcSwap<Config>>,
+    /// Client for the session server, shared to reuse connections.
+    pub http: reqwest::Client,
+    online_players: AtomicI32,
+}
+
+impl GatewayState {
+    pub fn new(config: Arc<ArcSwap<Config>>) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            online_players: AtomicI32::new(0),
+        }
+    }
+
+    pub fn online_players(&self) -> i32 {
+        self.online_players.load(Ordering::Relaxed)
+    }
+
+    /// Counts a player as online until the returned guard is dropped.
+    pub fn join(self: &Arc<Self>) -> PlayerGuard {
+        self.online_players.fetch_add(1, Ordering::Relaxed);
+        PlayerGuard(self.clone())
+    }
+}
+
+pub(crate) struct PlayerGuard(Arc<GatewayState>);
+
+impl Drop for PlayerGuard {
+    fn drop(&mut self) {
+        self.0.online_players.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,519 @@
+use std::{sync::Arc, time::Duration};
+
+use arc_swap::ArcSwap;
+use futures::{SinkExt, StreamExt};
+use minecrust_codec::{PacketCodec, packet::RawPacket};
+use minecrust_gateway::{Config, ServerKey, config::AuthMode};
+use minecrust_protocol::{
+    Limits,
+    datatype::{Content, GameProfile, Intent, TextComponent},
+    packet::{
+        unversioned::server::Intention,
+        v773::{
+            client::{
+                configuration::{ClientboundConfiguration, Disconnect},
+                login::{ClientboundLogin, LoginCompression, LoginDisconnect, LoginFinished},
+            },
+            server::{
+                configuration::SelectKnownPacks,
+                login::{Hello, Key, LoginAcknowledged},
+            },
+        },
+    },
+};
+use rsa::{Pkcs1v15Encrypt, RsaPublicKey, pkcs8::DecodePublicKey};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_util::{codec::Framed, sync::CancellationToken, task::TaskTracker};
+use uuid::Uuid;
+
+const PROFILE: &str = r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","properties":[{"name":"textures","value":"e30=","signature":"c2ln"}]}"#;
+
+fn config() -> Config {
+    Config::new(Arc::new(ServerKey::generate().unwrap()))
+}
+
+/// Starts a gateway on a free port and returns its address with its runtime config.
+async fn gateway(config: Config) -> (String, Arc<ArcSwap<Config>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let config = Arc::new(ArcSwap::from_pointee(config));
+    tokio::spawn(minecrust_gateway::serve(
+        CancellationToken::new(),
+        TaskTracker::new(),
+        listener,
+        config.clone(),
+    ));
+    (addr.to_string(), config)
+}
+
+/// Stands in for the session server, answering every request with `response`.
+async fn session_server(status: &'static str, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = vec![0u8; 4096];
+            let _ = stream.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    format!("http://{addr}")
+}
+
+async fn login(addr: &str, name: &str) -> Framed<TcpStream, PacketCodec> {
+    let mut stream = Framed::new(
+        TcpStream::connect(addr).await.unwrap(),
+        PacketCodec::default(),
+    );
+    stream
+        .send(
+            RawPacket::encode(&Intention {
+                protocol_version: 773,
+                server_address: "localhost".to_string(),
+                server_port: 25565,
+                intent: Intent::Login,
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    stream
+        .send(
+            RawPacket::encode(&Hello {
+                name: name.to_string(),
+                player_uuid: Uuid::nil(),
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    stream
+}
+
+async fn receive(stream: &mut Framed<TcpStream, PacketCodec>) -> RawPacket {
+    stream.next().await.unwrap().unwrap()
+}
+
+async fn receive_login(stream: &mut Framed<TcpStream, PacketCodec>) -> ClientboundLogin {
+    ClientboundLogin::decode(receive(stream).await, &Limits::DEFAULT).unwrap()
+}
+
+async fn encryption_request(
+    stream: &mut Framed<TcpStream, PacketCodec>,
+) -> (RsaPublicKey, Vec<u8>) {
+    let packet = receive_login(stream).await;
+    let ClientboundLogin::Hello(request) = packet else {
+        panic!("expected encryption request, got {packet:?}");
+    };
+    assert!(request.should_authenticate);
+
+    (
+        RsaPublicKey::from_public_key_der(&request.public_key).unwrap(),
+        request.verify_token.to_vec(),
+    )
+}
+
+async fn answer_encryption(
+    stream: &mut Framed<TcpStream, PacketCodec>,
+    public_key: &RsaPublicKey,
+    verify_token: &[u8],
+) {
+    let shared_secret = [7u8; 16];
+    let rng = &mut rand::thread_rng();
+    stream
+        .send(
+            RawPacket::encode(&Key {
+                shared_secret: public_key
+                    .encrypt(rng, Pkcs1v15Encrypt, &shared_secret)
+                    .unwrap(),
+                verify_token: public_key
+                    .encrypt(rng, Pkcs1v15Encrypt, verify_token)
+                    .unwrap(),
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    stream.codec_mut().enable_crypto(&shared_secret);
+}
+
+/// Answers the encryption request like a client would.
+async fn encrypt(stream: &mut Framed<TcpStream, PacketCodec>) {
+    let (public_key, verify_token) = encryption_request(stream).await;
+    answer_encryption(stream, &public_key, &verify_token).await;
+}
+
+async fn finish(stream: &mut Framed<TcpStream, PacketCodec>) -> GameProfile {
+    let packet = receive_login(stream).await;
+    let ClientboundLogin::LoginCompression(LoginCompression(threshold)) = packet else {
+        panic!("expected compression, got {packet:?}");
+    };
+    stream.codec_mut().enable_compression(threshold as usize);
+    let packet = receive_login(stream).await;
+    let ClientboundLogin::LoginFinished(LoginFinished(profile)) = packet else {
+        panic!("expected login success, got {packet:?}");
+    };
+    profile
+}
+
+#[tokio::test]
+async fn test_offline_login() {
+    let (addr, _) = gateway(Config {
+        auth_mode: AuthMode::Offline,
+        ..config()
+    })
+    .await;
+
+    let mut stream = login(&addr, "Notch").await;
+    let profile = finish(&mut stream).await;
+
+    assert_eq!(profile.username, "Notch");
+    assert_eq!(
+        profile.uuid,
+        Uuid::parse_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap()
+    );
+    assert!(profile.properties.is_empty());
+}
+
+#[tokio::test]
+async fn test_online_login() {
+    let (addr, _) = gateway(Config {
+        session_server: session_server("200 OK", PROFILE).await,
+        ..config()
+    })
+    .await;
+
+    let mut stream = login(&addr, "Notch").await;
+    encrypt(&mut stream).await;
+    let profile = finish(&mut stream).await;
+
+    assert_eq!(
+        profile.uuid,
+        Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap()
+    );
+    assert_eq!(profile.properties[0].name, "textures");
+    assert_eq!(profile.properties[0].value, "e30=");
+    assert_eq!(profile.properties[0].signature.as_deref(), Some("c2ln"));
+}
+
+#[tokio::test]
+async fn test_online_login_unverified() {
+    let (addr, _) = gateway(Config {
+        session_server: session_server("204 No Content", "").await,
+        ..config()
+    })
+    .await;
+
+    let mut stream = login(&addr, "Notch").await;
+    encrypt(&mut stream).await;
+
+    assert_eq!(
+        disconnected(&mut stream).await,
+        "multiplayer.disconnect.unverified_username"
+    );
+}
+
+#[tokio::test]
+async fn test_session_server_timeout() {
+    // Accepts connections but never answers them.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let session_server = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut streams = vec![];
+        while let Ok((stream, _)) = listener.accept().await {
+            streams.push(stream);
+        }
+    });
+    let (addr, _) = gateway(Config {
+        session_server,
+        session_timeout: Duration::from_millis(100),
+        ..config()
+    })
+    .await;
+
+    let mut stream = login(&addr, "Notch").await;
+    encrypt(&mut stream).await;
+
+    assert_eq!(
+        disconnected(&mut stream).await,
+        "multiplayer.disconnect.authservers_down"
+    );
+}
+
+#[tokio::test]
+async fn test_key_rotation_keeps_pending_logins() {
+    let (addr, config) = gateway(Config {
+        session_server: session_server("200 OK", PROFILE).await,
+        ..config()
+    })
+    .await;
+
+    let mut stream = login(&addr, "Notch").await;
+    let (public_key, verify_token) = encryption_request(&mut stream).await;
+    config.store(Arc::new(Config {
+        key: Arc::new(ServerKey::generate().unwrap()),
+        ..Config::clone(&config.load())
+    }));
+    answer_encryption(&mut stream, &public_key, &verify_token).await;
+
+    assert_eq!(finish(&mut stream).await.username, "Notch");
+}
+
+/// Reads the disconnect reason and expects the gateway to close the connection afterwards.
+async fn disconnected(stream: &mut Framed<TcpStream, PacketCodec>) -> String {
+    let packet = receive_login(stream).await;
+    let ClientboundLogin::LoginDisconnect(LoginDisconnect(reason)) = packet else {
+        panic!("expected disconnect, got {packet:?}");
+    };
+    assert!(stream.next().await.is_none());
+    match reason.content {
+        Content::Translatable { key, .. } => key,
+        content => panic!("unexpected disconnect reason {content:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_verify_token_mismatch() {
+    let (addr, _) = gateway(config()).await;
+
+    let mut stream = login(&addr, "Notch").await;
+    let (public_key, _) = encryption_request(&mut stream).await;
+    let rng = &mut rand::thread_rng();
+    stream
+        .send(
+            RawPacket::encode(&Key {
+                shared_secret: public_key
+                    .encrypt(rng, Pkcs1v15Encrypt, &[7u8; 16])
+                    .unwrap(),
+                verify_token: public_key.encrypt(rng, Pkcs1v15Encrypt, &[0; 4]).unwrap(),
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(disconnected(&mut stream).await, "disconnect.packetError");
+}
+
+#[tokio::test]
+async fn test_key_before_hello() {
+    let (addr, _) = gateway(config()).await;
+
+    let mut stream = Framed::new(
+        TcpStream::connect(&addr).await.unwrap(),
+        PacketCodec::default(),
+    );
+    stream
+        .send(
+            RawPacket::encode(&Intention {
+                protocol_version: 773,
+                server_address: "localhost".to_string(),
+                server_port: 25565,
+                intent: Intent::Login,
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    stream
+        .send(
+            RawPacket::encode(&Key {
+                shared_secret: vec![0; 128],
+                verify_token: vec![0; 128],
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(disconnected(&mut stream).await, "disconnect.packetError");
+}
+
+#[tokio::test]
+async fn test_undecryptable_key() {
+    let (addr, _) = gateway(config()).await;
+
+    let mut stream = login(&addr, "Notch").await;
+    encryption_request(&mut stream).await;
+    stream
+        .send(
+            RawPacket::encode(&Key {
+                shared_secret: vec![0; 128],
+                verify_token: vec![0; 128],
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(disconnected(&mut stream).await, "disconnect.packetError");
+}
+
+#[tokio::test]
+async fn test_short_shared_secret() {
+    let (addr, _) = gateway(config()).await;
+
+    let mut stream = login(&addr, "Notch").await;
+    let (public_key, verify_token) = encryption_request(&mut stream).await;
+    let rng = &mut rand::thread_rng();
+    stream
+        .send(
+            RawPacket::encode(&Key {
+                shared_secret: public_key
+                    .encrypt(rng, Pkcs1v15Encrypt, &[7u8; 15])
+                    .unwrap(),
+                verify_token: public_key
+                    .encrypt(rng, Pkcs1v15Encrypt, &verify_token)
+                    .unwrap(),
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(disconnected(&mut stream).await, "disconnect.packetError");
+}
+
+/// Starts a login with `protocol_version` and returns the disconnect reason with its argument.
+async fn login_unsupported(protocol_version: i32) -> (String, String) {
+    let (addr, _) = gateway(config()).await;
+
+    let mut stream = Framed::new(
+        TcpStream::connect(&addr).await.unwrap(),
+        PacketCodec::default(),
+    );
+    stream
+        .send(
+            RawPacket::encode(&Intention {
+                protocol_version,
+                server_address: "localhost".to_string(),
+                server_port: 25565,
+                intent: Intent::Login,
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let packet = receive_login(&mut stream).await;
+    let ClientboundLogin::LoginDisconnect(LoginDisconnect(reason)) = packet else {
+        panic!("expected disconnect, got {packet:?}");
+    };
+    assert!(stream.next().await.is_none());
+    match reason.content {
+        Content::Translatable { key, with, .. } => (key, with[0].to_plain()),
+        content => panic!("unexpected disconnect reason {content:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_outdated_client() {
+    assert_eq!(
+        login_unsupported(47).await,
+        (
+            "multiplayer.disconnect.outdated_client".to_string(),
+            "1.21.9-1.21.10".to_string()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_outdated_server() {
+    let (key, _) = login_unsupported(774).await;
+    assert_eq!(key, "multiplayer.disconnect.outdated_server");
+}
+
+#[tokio::test]
+async fn test_transfer_disconnects() {
+    let (addr, _) = gateway(config()).await;
+
+    let mut stream = Framed::new(
+        TcpStream::connect(&addr).await.unwrap(),
+        PacketCodec::default(),
+    );
+    stream
+        .send(
+            RawPacket::encode(&Intention {
+                protocol_version: 773,
+                server_address: "localhost".to_string(),
+                server_port: 25565,
+                intent: Intent::Transfer,
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        disconnected(&mut stream).await,
+        "multiplayer.disconnect.transfers_disabled"
+    );
+}
+
+#[tokio::test]
+async fn test_configuration_error_disconnects() {
+    let (addr, _) = gateway(Config {
+        auth_mode: AuthMode::Offline,
+        ..config()
+    })
+    .await;
+
+    let mut stream = login(&addr, "Notch").await;
+    finish(&mut stream).await;
+    stream
+        .send(RawPacket::encode(&LoginAcknowledged).unwrap())
+        .await
+        .unwrap();
+    stream
+        .send(RawPacket::encode(&SelectKnownPacks(vec![])).unwrap())
+        .await
+        .unwrap();
+
+    let packet =
+        ClientboundConfiguration::decode(receive(&mut stream).await, &Limits::DEFAULT).unwrap();
+    let ClientboundConfiguration::Disconnect(Disconnect(reason)) = packet else {
+        panic!("expected disconnect, got {packet:?}");
+    };
+    assert!(stream.next().await.is_none());
+    assert!(matches!(
+        reason.content,
+        Content::Translatable { key, .. } if key == "disconnect.packetError"
+    ));
+}
+
+#[tokio::test]
+async fn test_shutdown_disconnects_players() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let token = CancellationToken::new();
+    let tracker = TaskTracker::new();
+    tokio::spawn(minecrust_gateway::serve(
+        token.clone(),
+        tracker.clone(),
+        listener,
+        Arc::new(ArcSwap::from_pointee(Config {
+            shutdown_message: TextComponent::text("Restarting"),
+            ..config()
+        })),
+    ));
+
+    let mut stream = login(&addr, "Notch").await;
+    encryption_request(&mut stream).await;
+    token.cancel();
+
+    let packet = receive_login(&mut stream).await;
+    let ClientboundLogin::LoginDisconnect(LoginDisconnect(reason)) = packet else {
+        panic!("expected disconnect, got {packet:?}");
+    };
+    assert_eq!(reason.to_plain(), "Restarting");
+    assert!(stream.next().await.is_none());
+    tracker.close();
+    tracker.wait().await;
+}
@@ -1,14 +1,14 @@
-use minecrust_protocol_macro::Serialize;
+use minecrust_protocol_macro::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct GameProfileProperties {
     pub name: String,
     pub value: String,
     pub signature: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct GameProfile {
     pub uuid: Uuid,
     pub username: String,
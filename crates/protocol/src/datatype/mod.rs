@@ -1,11 +1,17 @@
 mod game_profile;
 mod intent;
-mod text_component;
+mod known_pack;
+pub mod nbt;
+mod server_status;
+pub mod text_component;
 pub mod var_int;
 mod var_long;
 
 pub use game_profile::*;
 pub use intent::*;
+pub use known_pack::*;
+pub use nbt::{Nbt, NbtCompound, NbtLimits, NbtList};
+pub use server_status::*;
 pub use text_component::*;
 pub use var_long::*;
 
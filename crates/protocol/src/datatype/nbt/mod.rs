@@ -0,0 +1,649 @@
+//! Named Binary Tag values in their network encoding, where the root tag carries no name.
+
+use std::collections::BTreeMap;
+
+use bytes::{Buf, BufMut};
+
+use crate::{Deserialize, Error, Limits, Serialize};
+
+mod mutf8;
+mod snbt;
+
+pub const TAG_END: u8 = 0;
+pub const TAG_BYTE: u8 = 1;
+pub const TAG_SHORT: u8 = 2;
+pub const TAG_INT: u8 = 3;
+pub const TAG_LONG: u8 = 4;
+pub const TAG_FLOAT: u8 = 5;
+pub const TAG_DOUBLE: u8 = 6;
+pub const TAG_BYTE_ARRAY: u8 = 7;
+pub const TAG_STRING: u8 = 8;
+pub const TAG_LIST: u8 = 9;
+pub const TAG_COMPOUND: u8 = 10;
+pub const TAG_INT_ARRAY: u8 = 11;
+pub const TAG_LONG_ARRAY: u8 = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nbt {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(NbtList),
+    Compound(NbtCompound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+/// List of tags that all share the same type.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NbtList {
+    items: Vec<Nbt>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NbtCompound(BTreeMap<String, Nbt>);
+
+/// Bounds applied while reading untrusted NBT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NbtLimits {
+    /// Maximum nesting of lists and compounds.
+    pub max_depth: usize,
+    /// Maximum number of bytes the decoded value may account for.
+    pub max_size: usize,
+}
+
+impl NbtLimits {
+    pub const DEFAULT: Self = Self {
+        max_depth: 512,
+        max_size: 2 * 1024 * 1024,
+    };
+}
+
+impl Default for NbtLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Nbt {
+    pub fn tag_type(&self) -> u8 {
+        match self {
+            Nbt::Byte(_) => TAG_BYTE,
+            Nbt::Short(_) => TAG_SHORT,
+            Nbt::Int(_) => TAG_INT,
+            Nbt::Long(_) => TAG_LONG,
+            Nbt::Float(_) => TAG_FLOAT,
+            Nbt::Double(_) => TAG_DOUBLE,
+            Nbt::ByteArray(_) => TAG_BYTE_ARRAY,
+            Nbt::String(_) => TAG_STRING,
+            Nbt::List(_) => TAG_LIST,
+            Nbt::Compound(_) => TAG_COMPOUND,
+            Nbt::IntArray(_) => TAG_INT_ARRAY,
+            Nbt::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    /// Reads a nameless root tag, where a lone end tag stands for no value.
+    pub fn read_optional<B: Buf>(buf: &mut B, limits: NbtLimits) -> Result<Option<Self>, Error> {
+        let tag_type = buf.try_get_u8()?;
+        if tag_type == TAG_END {
+            return Ok(None);
+        }
+
+        let mut reader = Reader {
+            buf,
+            limits,
+            size: 0,
+        };
+        reader.read_payload(tag_type, 0).map(Some)
+    }
+
+    /// Reads a nameless root tag.
+    pub fn read<B: Buf>(buf: &mut B, limits: NbtLimits) -> Result<Self, Error> {
+        Self::read_optional(buf, limits)?.ok_or(Error::InvalidNbtTag(TAG_END))
+    }
+
+    fn write_payload<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        match self {
+            Nbt::Byte(value) => buf.put_i8(*value),
+            Nbt::Short(value) => buf.put_i16(*value),
+            Nbt::Int(value) => buf.put_i32(*value),
+            Nbt::Long(value) => buf.put_i64(*value),
+            Nbt::Float(value) => buf.put_f32(*value),
+            Nbt::Double(value) => buf.put_f64(*value),
+            Nbt::ByteArray(values) => {
+                buf.put_i32(values.len() as i32);
+                for value in values {
+                    buf.put_i8(*value);
+                }
+            }
+            Nbt::String(value) => write_string(value, buf)?,
+            Nbt::List(list) => {
+                buf.put_u8(list.element_type());
+                buf.put_i32(list.len() as i32);
+                for item in list.iter() {
+                    item.write_payload(buf)?;
+                }
+            }
+            Nbt::Compound(compound) => {
+                for (key, value) in compound.iter() {
+                    buf.put_u8(value.tag_type());
+                    write_string(key, buf)?;
+                    value.write_payload(buf)?;
+                }
+                buf.put_u8(TAG_END);
+            }
+            Nbt::IntArray(values) => {
+                buf.put_i32(values.len() as i32);
+                for value in values {
+                    buf.put_i32(*value);
+                }
+            }
+            Nbt::LongArray(values) => {
+                buf.put_i32(values.len() as i32);
+                for value in values {
+                    buf.put_i64(*value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Maximum length of a string in modified UTF-8, bounded by its `u16` length prefix.
+pub const MAX_STRING_LENGTH: usize = u16::MAX as usize;
+
+/// Writes `value` in modified UTF-8, failing if it does not fit the length prefix.
+fn write_string<B: BufMut>(value: &str, buf: &mut B) -> Result<(), Error> {
+    let bytes = mutf8::encode(value);
+    if bytes.len() > MAX_STRING_LENGTH {
+        return Err(Error::StringTooLong {
+            length: bytes.len(),
+            max: MAX_STRING_LENGTH,
+        });
+    }
+
+    buf.put_u16(bytes.len() as u16);
+    buf.put_slice(&bytes);
+    Ok(())
+}
+
+struct Reader<'a, B> {
+    buf: &'a mut B,
+    limits: NbtLimits,
+    size: usize,
+}
+
+impl<B: Buf> Reader<'_, B> {
+    fn account(&mut self, bytes: usize) -> Result<(), Error> {
+        self.size = self.size.saturating_add(bytes);
+        if self.size > self.limits.max_size {
+            return Err(Error::NbtTooLarge(self.limits.max_size));
+        }
+        Ok(())
+    }
+
+    /// Reads a length prefix and ensures the buffer holds `len` elements of `element_size` bytes.
+    fn read_len(&mut self, element_size: usize) -> Result<usize, Error> {
+        let len = self.buf.try_get_i32()?;
+        let len = usize::try_from(len).map_err(|_| Error::Custom("negative nbt length"))?;
+        let bytes = len.saturating_mul(element_size);
+        if bytes > self.buf.remaining() {
+            return Err(Error::UnexpectedEof);
+        }
+        self.account(bytes)?;
+        Ok(len)
+    }
+
+    fn read_string(&mut self) -> Result<String, Error> {
+        let len = self.buf.try_get_u16()? as usize;
+        if len > self.buf.remaining() {
+            return Err(Error::UnexpectedEof);
+        }
+        self.account(len)?;
+
+        let mut bytes = vec![0u8; len];
+        self.buf.try_copy_to_slice(&mut bytes)?;
+        mutf8::decode(&bytes)
+    }
+
+    fn read_payload(&mut self, tag_type: u8, depth: usize) -> Result<Nbt, Error> {
+        self.account(size_of::<Nbt>())?;
+
+        Ok(match tag_type {
+            TAG_BYTE => Nbt::Byte(self.buf.try_get_i8()?),
+            TAG_SHORT => Nbt::Short(self.buf.try_get_i16()?),
+            TAG_INT => Nbt::Int(self.buf.try_get_i32()?),
+            TAG_LONG => Nbt::Long(self.buf.try_get_i64()?),
+            TAG_FLOAT => Nbt::Float(self.buf.try_get_f32()?),
+            TAG_DOUBLE => Nbt::Double(self.buf.try_get_f64()?),
+            TAG_BYTE_ARRAY => {
+                let len = self.read_len(1)?;
+                Nbt::ByteArray((0..len).map(|_| self.buf.get_i8()).collect())
+            }
+            TAG_STRING => Nbt::String(self.read_string()?),
+            TAG_LIST => {
+                if depth >= self.limits.max_depth {
+                    return Err(Error::NbtTooDeep(self.limits.max_depth));
+                }
+
+                let element_type = self.buf.try_get_u8()?;
+                let len = self.read_len(minimum_payload_size(element_type)?)?;
+                if element_type == TAG_END && len > 0 {
+                    return Err(Error::InvalidNbtTag(TAG_END));
+                }
+
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.read_payload(element_type, depth + 1)?);
+                }
+                Nbt::List(NbtList { items })
+            }
+            TAG_COMPOUND => {
+                if depth >= self.limits.max_depth {
+                    return Err(Error::NbtTooDeep(self.limits.max_depth));
+                }
+
+                let mut compound = NbtCompound::new();
+                loop {
+                    let tag_type = self.buf.try_get_u8()?;
+                    if tag_type == TAG_END {
+                        break;
+                    }
+                    let key = self.read_string()?;
+                    let value = self.read_payload(tag_type, depth + 1)?;
+                    compound.insert(key, value);
+                }
+                Nbt::Compound(compound)
+            }
+            TAG_INT_ARRAY => {
+                let len = self.read_len(4)?;
+                Nbt::IntArray((0..len).map(|_| self.buf.get_i32()).collect())
+            }
+            TAG_LONG_ARRAY => {
+                let len = self.read_len(8)?;
+                Nbt::LongArray((0..len).map(|_| self.buf.get_i64()).collect())
+            }
+            tag_type => return Err(Error::InvalidNbtTag(tag_type)),
+        })
+    }
+}
+
+/// Smallest number of bytes a payload of `tag_type` occupies on the wire.
+fn minimum_payload_size(tag_type: u8) -> Result<usize, Error> {
+    Ok(match tag_type {
+        TAG_END => 0,
+        TAG_BYTE => 1,
+        TAG_SHORT | TAG_STRING => 2,
+        TAG_INT | TAG_FLOAT | TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY => 4,
+        TAG_LONG | TAG_DOUBLE => 8,
+        TAG_LIST => 5,
+        TAG_COMPOUND => 1,
+        tag_type => return Err(Error::InvalidNbtTag(tag_type)),
+    })
+}
+
+impl NbtList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Type of the contained tags, [`TAG_END`] for an empty list.
+    pub fn element_type(&self) -> u8 {
+        self.items.first().map_or(TAG_END, Nbt::tag_type)
+    }
+
+    pub fn push(&mut self, item: impl Into<Nbt>) -> Result<(), Error> {
+        let item = item.into();
+        if !self.items.is_empty() && item.tag_type() != self.element_type() {
+            return Err(Error::Custom("nbt list elements must share one type"));
+        }
+        self.items.push(item);
+        Ok(())
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Nbt> {
+        self.items.get(index)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Nbt> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl TryFrom<Vec<Nbt>> for NbtList {
+    type Error = Error;
+
+    fn try_from(items: Vec<Nbt>) -> Result<Self, Self::Error> {
+        let mut list = Self::new();
+        for item in items {
+            list.push(item)?;
+        }
+        Ok(list)
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtList {
+    type Item = &'a Nbt;
+    type IntoIter = std::slice::Iter<'a, Nbt>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+macro_rules! typed_getter {
+    ($name:ident, $variant:ident, $ty:ty) => {
+        pub fn $name(&self, key: &str) -> Option<$ty> {
+            match self.0.get(key)? {
+                Nbt::$variant(value) => Some(*value),
+                _ => None,
+            }
+        }
+    };
+    ($name:ident, $variant:ident, ref $ty:ty) => {
+        pub fn $name(&self, key: &str) -> Option<&$ty> {
+            match self.0.get(key)? {
+                Nbt::$variant(value) => Some(value),
+                _ => None,
+            }
+        }
+    };
+}
+
+impl NbtCompound {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Nbt>) -> Option<Nbt> {
+        self.0.insert(key.into(), value.into())
+    }
+
+    /// Builder style variant of [`NbtCompound::insert`].
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<Nbt>) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Nbt> {
+        self.0.get(key)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Nbt> {
+        self.0.remove(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    pub fn iter(&self) -> std::collections::btree_map::Iter<'_, String, Nbt> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    typed_getter!(get_byte, Byte, i8);
+    typed_getter!(get_short, Short, i16);
+    typed_getter!(get_int, Int, i32);
+    typed_getter!(get_long, Long, i64);
+    typed_getter!(get_float, Float, f32);
+    typed_getter!(get_double, Double, f64);
+    typed_getter!(get_byte_array, ByteArray, ref [i8]);
+    typed_getter!(get_string, String, ref str);
+    typed_getter!(get_list, List, ref NbtList);
+    typed_getter!(get_compound, Compound, ref NbtCompound);
+    typed_getter!(get_int_array, IntArray, ref [i32]);
+    typed_getter!(get_long_array, LongArray, ref [i64]);
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get_byte(key).map(|value| value != 0)
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtCompound {
+    type Item = (&'a String, &'a Nbt);
+    type IntoIter = std::collections::btree_map::Iter<'a, String, Nbt>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Into<String>, V: Into<Nbt>> FromIterator<(K, V)> for NbtCompound {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+macro_rules! from_value {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Nbt {
+                fn from(value: $ty) -> Self {
+                    Nbt::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+from_value! {
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    Vec<i8> => ByteArray,
+    String => String,
+    &str => String,
+    NbtList => List,
+    NbtCompound => Compound,
+    Vec<i32> => IntArray,
+    Vec<i64> => LongArray,
+}
+
+impl From<bool> for Nbt {
+    fn from(value: bool) -> Self {
+        Nbt::Byte(value as i8)
+    }
+}
+
+/// Fails with [`Error::StringTooLong`] if a string or key exceeds [`MAX_STRING_LENGTH`] bytes
+/// in modified UTF-8.
+impl Serialize for Nbt {
+    fn serialize<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        buf.put_u8(self.tag_type());
+        self.write_payload(buf)
+    }
+}
+
+impl Deserialize for Nbt {
+    fn deserialize<B: Buf>(buf: &mut B, limits: &Limits) -> Result<Self, Error> {
+        Self::read(buf, limits.nbt)
+    }
+}
+
+/// Fails with [`Error::StringTooLong`] if a string or key exceeds [`MAX_STRING_LENGTH`] bytes
+/// in modified UTF-8.
+impl Serialize for NbtCompound {
+    fn serialize<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        buf.put_u8(TAG_COMPOUND);
+        for (key, value) in self.iter() {
+            buf.put_u8(value.tag_type());
+            write_string(key, buf)?;
+            value.write_payload(buf)?;
+        }
+        buf.put_u8(TAG_END);
+        Ok(())
+    }
+}
+
+impl Deserialize for NbtCompound {
+    fn deserialize<B: Buf>(buf: &mut B, limits: &Limits) -> Result<Self, Error> {
+        match Nbt::deserialize(buf, limits)? {
+            Nbt::Compound(compound) => Ok(compound),
+            nbt => Err(Error::InvalidNbtTag(nbt.tag_type())),
+        }
+    }
+}
+
+/// NBT that may be absent, encoded as a lone end tag, for use with
+/// `#[protocol(with = nbt::optional)]`.
+pub mod optional {
+    use bytes::{Buf, BufMut};
+
+    use super::{Nbt, TAG_END};
+    use crate::{Error, Limits, Serialize};
+
+    pub fn deserialize<B: Buf>(buf: &mut B, limits: &Limits) -> Result<Option<Nbt>, Error> {
+        Nbt::read_optional(buf, limits.nbt)
+    }
+
+    pub fn serialize<B: BufMut>(value: &Option<Nbt>, buf: &mut B) -> Result<(), Error> {
+        match value {
+            Some(nbt) => nbt.serialize(buf),
+            None => {
+                buf.put_u8(TAG_END);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use super::*;
+
+    fn sample() -> Nbt {
+        let mut list = NbtList::new();
+        list.push(1.5f32).unwrap();
+        list.push(-2.0f32).unwrap();
+
+        NbtCompound::new()
+            .with("byte", 1i8)
+            .with("short", 300i16)
+            .with("long", i64::MIN)
+            .with("double", 0.25f64)
+            .with("bytes", vec![1i8, -1])
+            .with("name", "Steve\0")
+            .with("floats", list)
+            .with("nested", NbtCompound::new().with("ints", vec![1i32, 2, 3]))
+            .with("longs", vec![7i64])
+            .into()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let nbt = sample();
+        let mut buf = BytesMut::new();
+        nbt.serialize(&mut buf).unwrap();
+
+        assert_eq!(Nbt::deserialize(&mut buf, &Limits::DEFAULT).unwrap(), nbt);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_nameless_root() {
+        let mut buf = BytesMut::new();
+        Nbt::from("hi").serialize(&mut buf).unwrap();
+        assert_eq!(&buf[..], &[TAG_STRING, 0x00, 0x02, b'h', b'i']);
+
+        let mut buf = BytesMut::new();
+        NbtCompound::new()
+            .with("a", 1i8)
+            .serialize(&mut buf)
+            .unwrap();
+        assert_eq!(
+            &buf[..],
+            &[TAG_COMPOUND, TAG_BYTE, 0x00, 0x01, b'a', 0x01, TAG_END]
+        );
+    }
+
+    #[test]
+    fn test_string_too_long() {
+        let mut buf = BytesMut::new();
+        assert!(matches!(
+            Nbt::from("a".repeat(MAX_STRING_LENGTH + 1)).serialize(&mut buf),
+            Err(Error::StringTooLong {
+                length: 65536,
+                max: MAX_STRING_LENGTH
+            })
+        ));
+
+        let compound = NbtCompound::new().with("a".repeat(MAX_STRING_LENGTH + 1), 1i8);
+        assert!(matches!(
+            compound.serialize(&mut buf),
+            Err(Error::StringTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let mut buf = BytesMut::new();
+        buf.put_u8(TAG_LIST);
+        for _ in 0..600 {
+            buf.put_u8(TAG_LIST);
+            buf.put_i32(1);
+        }
+        buf.put_u8(TAG_END);
+        buf.put_i32(0);
+
+        assert!(matches!(
+            Nbt::deserialize(&mut buf, &Limits::DEFAULT),
+            Err(Error::NbtTooDeep(512))
+        ));
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let limits = NbtLimits {
+            max_depth: 512,
+            max_size: 1024,
+        };
+        let mut buf = BytesMut::new();
+        Nbt::ByteArray(vec![0; 4096]).serialize(&mut buf).unwrap();
+        assert!(matches!(
+            Nbt::read(&mut buf, limits),
+            Err(Error::NbtTooLarge(1024))
+        ));
+    }
+
+    #[test]
+    fn test_length_exceeding_buffer() {
+        let mut buf = BytesMut::new();
+        buf.put_u8(TAG_LONG_ARRAY);
+        buf.put_i32(i32::MAX);
+        assert!(matches!(
+            Nbt::deserialize(&mut buf, &Limits::DEFAULT),
+            Err(Error::UnexpectedEof)
+        ));
+    }
+}
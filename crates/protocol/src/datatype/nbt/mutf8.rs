@@ -0,0 +1,105 @@
+//! Java's modified UTF-8, used for all NBT strings.
+//!
+//! It differs from UTF-8 in encoding `U+0000` as two bytes and characters outside the basic
+//! multilingual plane as a pair of three-byte encoded surrogates.
+
+use crate::Error;
+
+pub(crate) fn encoded_len(value: &str) -> usize {
+    value
+        .chars()
+        .map(|char| match char as u32 {
+            0x01..=0x7F => 1,
+            0x00 | 0x80..=0x7FF => 2,
+            0x800..=0xFFFF => 3,
+            _ => 6,
+        })
+        .sum()
+}
+
+pub(crate) fn encode(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(encoded_len(value));
+    let mut units = [0u16; 2];
+    for char in value.chars() {
+        for unit in char.encode_utf16(&mut units) {
+            let unit = *unit as u32;
+            match unit {
+                0x01..=0x7F => bytes.push(unit as u8),
+                0x00 | 0x80..=0x7FF => {
+                    bytes.push(0xC0 | (unit >> 6) as u8);
+                    bytes.push(0x80 | (unit & 0x3F) as u8);
+                }
+                _ => {
+                    bytes.push(0xE0 | (unit >> 12) as u8);
+                    bytes.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                    bytes.push(0x80 | (unit & 0x3F) as u8);
+                }
+            }
+        }
+    }
+    bytes
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<String, Error> {
+    const INVALID: Error = Error::Custom("invalid modified utf-8 string");
+
+    let continuation = |index: usize| match bytes.get(index) {
+        Some(byte) if byte & 0xC0 == 0x80 => Ok((byte & 0x3F) as u16),
+        _ => Err(INVALID),
+    };
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = bytes[index];
+        let unit = match byte {
+            0x01..=0x7F => {
+                index += 1;
+                byte as u16
+            }
+            0xC0..=0xDF => {
+                let unit = ((byte & 0x1F) as u16) << 6 | continuation(index + 1)?;
+                index += 2;
+                unit
+            }
+            0xE0..=0xEF => {
+                let unit = ((byte & 0x0F) as u16) << 12
+                    | continuation(index + 1)? << 6
+                    | continuation(index + 2)?;
+                index += 3;
+                unit
+            }
+            _ => return Err(INVALID),
+        };
+        units.push(unit);
+    }
+
+    String::from_utf16(&units).map_err(|_| INVALID)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_CASES: [(&str, &[u8]); 4] = [
+        ("minecraft", b"minecraft"),
+        ("\0", &[0xC0, 0x80]),
+        ("\u{e9}", &[0xC3, 0xA9]),
+        ("\u{1F600}", &[0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]),
+    ];
+
+    #[test]
+    fn test_encode() {
+        for (value, bytes) in TEST_CASES {
+            assert_eq!(encode(value), bytes);
+            assert_eq!(encoded_len(value), bytes.len());
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        for (value, bytes) in TEST_CASES {
+            assert_eq!(decode(bytes).unwrap(), value);
+        }
+    }
+}
@@ -0,0 +1,607 @@
+//! Stringified NBT, the textual notation used by commands and data packs.
+//!
+//! [`Display`](fmt::Display) prints the compact form, [`Nbt::to_pretty_snbt`] an indented one.
+//! Both parse back into the exact same value through [`FromStr`].
+
+use std::{fmt, str::FromStr};
+
+use super::{MAX_STRING_LENGTH, Nbt, NbtCompound, NbtLimits, NbtList, mutf8};
+use crate::Error;
+
+const INDENT: &str = "    ";
+
+impl Nbt {
+    /// Renders the value as indented, multi-line SNBT.
+    pub fn to_pretty_snbt(&self) -> String {
+        let mut output = String::new();
+        Printer {
+            output: &mut output,
+            pretty: true,
+        }
+        .write_value(self, 0)
+        .expect("writing to a string does not fail");
+        output
+    }
+}
+
+impl fmt::Display for Nbt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Printer {
+            output: f,
+            pretty: false,
+        }
+        .write_value(self, 0)
+    }
+}
+
+impl fmt::Display for NbtCompound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Printer {
+            output: f,
+            pretty: false,
+        }
+        .write_compound(self, 0)
+    }
+}
+
+impl fmt::Display for NbtList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Printer {
+            output: f,
+            pretty: false,
+        }
+        .write_list(self, 0)
+    }
+}
+
+impl FromStr for Nbt {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            input,
+            position: 0,
+            max_depth: NbtLimits::default().max_depth,
+        };
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.position != input.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+}
+
+impl FromStr for NbtCompound {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.parse()? {
+            Nbt::Compound(compound) => Ok(compound),
+            _ => Err(Error::Snbt {
+                position: 0,
+                message: "expected compound",
+            }),
+        }
+    }
+}
+
+struct Printer<W> {
+    output: W,
+    pretty: bool,
+}
+
+impl<W: fmt::Write> Printer<W> {
+    fn write_value(&mut self, value: &Nbt, depth: usize) -> fmt::Result {
+        match value {
+            Nbt::Byte(value) => write!(self.output, "{value}b"),
+            Nbt::Short(value) => write!(self.output, "{value}s"),
+            Nbt::Int(value) => write!(self.output, "{value}"),
+            Nbt::Long(value) => write!(self.output, "{value}L"),
+            Nbt::Float(value) => self.write_float(*value as f64, format!("{value:?}"), 'f'),
+            Nbt::Double(value) => self.write_float(*value, format!("{value:?}"), 'd'),
+            Nbt::ByteArray(values) => self.write_array('B', values, "b"),
+            Nbt::String(value) => self.write_string(value),
+            Nbt::List(list) => self.write_list(list, depth),
+            Nbt::Compound(compound) => self.write_compound(compound, depth),
+            Nbt::IntArray(values) => self.write_array('I', values, ""),
+            Nbt::LongArray(values) => self.write_array('L', values, "L"),
+        }
+    }
+
+    fn write_float(&mut self, value: f64, display: String, suffix: char) -> fmt::Result {
+        if value.is_nan() {
+            write!(self.output, "NaN{suffix}")
+        } else if value.is_infinite() {
+            let sign = if value < 0.0 { "-" } else { "" };
+            write!(self.output, "{sign}Infinity{suffix}")
+        } else {
+            write!(self.output, "{display}{suffix}")
+        }
+    }
+
+    fn write_array<T: fmt::Display>(
+        &mut self,
+        prefix: char,
+        values: &[T],
+        suffix: &str,
+    ) -> fmt::Result {
+        write!(self.output, "[{prefix};")?;
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 {
+                self.output.write_char(',')?;
+            }
+            if self.pretty {
+                self.output.write_char(' ')?;
+            }
+            write!(self.output, "{value}{suffix}")?;
+        }
+        self.output.write_char(']')
+    }
+
+    fn write_string(&mut self, value: &str) -> fmt::Result {
+        let quote = if value.contains('"') && !value.contains('\'') {
+            '\''
+        } else {
+            '"'
+        };
+
+        self.output.write_char(quote)?;
+        for char in value.chars() {
+            match char {
+                '\\' => self.output.write_str("\\\\")?,
+                '\n' => self.output.write_str("\\n")?,
+                '\t' => self.output.write_str("\\t")?,
+                '\r' => self.output.write_str("\\r")?,
+                char if char == quote => write!(self.output, "\\{quote}")?,
+                char if char.is_control() => write!(self.output, "\\u{:04x}", char as u32)?,
+                char => self.output.write_char(char)?,
+            }
+        }
+        self.output.write_char(quote)
+    }
+
+    fn write_key(&mut self, key: &str) -> fmt::Result {
+        if !key.is_empty() && key.chars().all(is_unquoted_char) {
+            self.output.write_str(key)
+        } else {
+            self.write_string(key)
+        }
+    }
+
+    fn write_separator(&mut self, index: usize, depth: usize) -> fmt::Result {
+        if index > 0 {
+            self.output.write_char(',')?;
+        }
+        if self.pretty {
+            self.output.write_char('\n')?;
+            for _ in 0..depth {
+                self.output.write_str(INDENT)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_closing(&mut self, is_empty: bool, depth: usize, close: char) -> fmt::Result {
+        if self.pretty && !is_empty {
+            self.output.write_char('\n')?;
+            for _ in 0..depth {
+                self.output.write_str(INDENT)?;
+            }
+        }
+        self.output.write_char(close)
+    }
+
+    fn write_list(&mut self, list: &NbtList, depth: usize) -> fmt::Result {
+        self.output.write_char('[')?;
+        for (index, item) in list.iter().enumerate() {
+            self.write_separator(index, depth + 1)?;
+            self.write_value(item, depth + 1)?;
+        }
+        self.write_closing(list.is_empty(), depth, ']')
+    }
+
+    fn write_compound(&mut self, compound: &NbtCompound, depth: usize) -> fmt::Result {
+        self.output.write_char('{')?;
+        for (index, (key, value)) in compound.iter().enumerate() {
+            self.write_separator(index, depth + 1)?;
+            self.write_key(key)?;
+            self.output
+                .write_str(if self.pretty { ": " } else { ":" })?;
+            self.write_value(value, depth + 1)?;
+        }
+        self.write_closing(compound.is_empty(), depth, '}')
+    }
+}
+
+fn is_unquoted_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || matches!(char, '_' | '-' | '.' | '+')
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    max_depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> Error {
+        Error::Snbt {
+            position: self.position,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.position += char.len_utf8();
+        Some(char)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char, message: &'static str) -> Result<(), Error> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(char) if char == expected => Ok(()),
+            _ => Err(self.error(message)),
+        }
+    }
+
+    /// Consumes `separator` if present and tells whether another element follows.
+    fn has_next(&mut self, close: char) -> Result<bool, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.next();
+                Ok(true)
+            }
+            Some(char) if char == close => Ok(false),
+            _ => Err(self.error("expected ',' or closing bracket")),
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Nbt, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => {
+                if depth >= self.max_depth {
+                    return Err(Error::NbtTooDeep(self.max_depth));
+                }
+                self.parse_compound(depth).map(Nbt::Compound)
+            }
+            Some('[') => {
+                if depth >= self.max_depth {
+                    return Err(Error::NbtTooDeep(self.max_depth));
+                }
+                self.parse_list_or_array(depth)
+            }
+            Some('"' | '\'') => self.parse_quoted().map(Nbt::String),
+            Some(_) => self.parse_unquoted_value(),
+            None => Err(self.error("expected value")),
+        }
+    }
+
+    fn parse_compound(&mut self, depth: usize) -> Result<NbtCompound, Error> {
+        self.expect('{', "expected '{'")?;
+        let mut compound = NbtCompound::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(compound);
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('"' | '\'') => self.parse_quoted()?,
+                _ => match self.parse_unquoted()? {
+                    "" => return Err(self.error("expected key")),
+                    key => key.to_string(),
+                },
+            };
+            self.expect(':', "expected ':'")?;
+            let value = self.parse_value(depth + 1)?;
+            compound.insert(key, value);
+
+            if !self.has_next('}')? {
+                break;
+            }
+        }
+        self.expect('}', "expected '}'")?;
+        Ok(compound)
+    }
+
+    fn parse_list_or_array(&mut self, depth: usize) -> Result<Nbt, Error> {
+        self.expect('[', "expected '['")?;
+
+        let rest = &self.input[self.position..];
+        let array_type = match rest.as_bytes() {
+            [prefix @ (b'B' | b'I' | b'L'), b';', ..] => Some(*prefix),
+            _ => None,
+        };
+        if let Some(array_type) = array_type {
+            self.position += 2;
+            return self.parse_array(array_type);
+        }
+
+        let mut list = NbtList::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(Nbt::List(list));
+        }
+
+        loop {
+            let position = self.position;
+            let value = self.parse_value(depth + 1)?;
+            list.push(value).map_err(|_| Error::Snbt {
+                position,
+                message: "list elements must share one type",
+            })?;
+
+            if !self.has_next(']')? {
+                break;
+            }
+        }
+        self.expect(']', "expected ']'")?;
+        Ok(Nbt::List(list))
+    }
+
+    fn parse_array(&mut self, array_type: u8) -> Result<Nbt, Error> {
+        let mut bytes = vec![];
+        let mut ints = vec![];
+        let mut longs = vec![];
+
+        self.skip_whitespace();
+        if self.peek() != Some(']') {
+            loop {
+                self.skip_whitespace();
+                let value = self.parse_unquoted_value()?;
+                match (array_type, value) {
+                    (b'B', Nbt::Byte(value)) => bytes.push(value),
+                    (b'I', Nbt::Int(value)) => ints.push(value),
+                    (b'L', Nbt::Long(value)) => longs.push(value),
+                    _ => return Err(self.error("array element does not match array type")),
+                }
+
+                if !self.has_next(']')? {
+                    break;
+                }
+            }
+        }
+        self.expect(']', "expected ']'")?;
+
+        Ok(match array_type {
+            b'B' => Nbt::ByteArray(bytes),
+            b'I' => Nbt::IntArray(ints),
+            _ => Nbt::LongArray(longs),
+        })
+    }
+
+    fn parse_quoted(&mut self) -> Result<String, Error> {
+        let Some(quote) = self.next() else {
+            return Err(self.error("expected string"));
+        };
+
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("unterminated string")),
+                Some(char) if char == quote => return self.check_length(value),
+                Some('\\') => match self.next() {
+                    Some(char @ ('\\' | '"' | '\'')) => value.push(char),
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('u') => {
+                        let end = self.position + 4;
+                        let escaped = self
+                            .input
+                            .get(self.position..end)
+                            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("invalid unicode escape"))?;
+                        self.position = end;
+                        value.push(escaped);
+                    }
+                    _ => return Err(self.error("invalid escape sequence")),
+                },
+                Some(char) => value.push(char),
+            }
+        }
+    }
+
+    fn parse_unquoted(&mut self) -> Result<&str, Error> {
+        let start = self.position;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.next();
+        }
+        self.check_length(&self.input[start..self.position])
+    }
+
+    /// Rejects strings that would not fit the length prefix of the binary encoding.
+    fn check_length<S: AsRef<str>>(&self, value: S) -> Result<S, Error> {
+        if mutf8::encoded_len(value.as_ref()) > MAX_STRING_LENGTH {
+            return Err(self.error("string too long"));
+        }
+        Ok(value)
+    }
+
+    fn parse_unquoted_value(&mut self) -> Result<Nbt, Error> {
+        let start = self.position;
+        let token = self.parse_unquoted()?;
+        if token.is_empty() {
+            return Err(self.error("expected value"));
+        }
+
+        let value = match token {
+            "true" => Some(Ok(Nbt::Byte(1))),
+            "false" => Some(Ok(Nbt::Byte(0))),
+            _ => parse_number(token),
+        };
+        match value {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(message)) => Err(Error::Snbt {
+                position: start,
+                message,
+            }),
+            None => Ok(Nbt::String(token.to_string())),
+        }
+    }
+}
+
+/// Parses `token` if it looks like a number. Anything else is an unquoted string.
+fn parse_number(token: &str) -> Option<Result<Nbt, &'static str>> {
+    const OUT_OF_RANGE: &str = "number out of range";
+
+    let (body, suffix) = match token.char_indices().last()? {
+        (index, suffix @ ('b' | 'B' | 's' | 'S' | 'l' | 'L' | 'f' | 'F' | 'd' | 'D')) => {
+            (&token[..index], Some(suffix.to_ascii_lowercase()))
+        }
+        _ => (token, None),
+    };
+
+    let unsigned = body.strip_prefix(['-', '+']).unwrap_or(body);
+    let is_integer = !unsigned.is_empty() && unsigned.chars().all(|char| char.is_ascii_digit());
+    let is_special = matches!(unsigned, "NaN" | "Infinity");
+    let is_decimal = is_special
+        || unsigned.chars().any(|char| char.is_ascii_digit())
+            && unsigned
+                .chars()
+                .all(|char| char.is_ascii_digit() || matches!(char, '.' | 'e' | 'E' | '-' | '+'))
+            && unsigned.matches('.').count() <= 1;
+
+    let value = match suffix {
+        Some('b') if is_integer => body.parse().map(Nbt::Byte).map_err(|_| OUT_OF_RANGE),
+        Some('s') if is_integer => body.parse().map(Nbt::Short).map_err(|_| OUT_OF_RANGE),
+        Some('l') if is_integer => body.parse().map(Nbt::Long).map_err(|_| OUT_OF_RANGE),
+        Some('f') if is_decimal => body.parse().map(Nbt::Float).map_err(|_| "invalid float"),
+        Some('d') if is_decimal => body.parse().map(Nbt::Double).map_err(|_| "invalid double"),
+        None if is_integer => body.parse().map(Nbt::Int).map_err(|_| OUT_OF_RANGE),
+        None if is_decimal && !is_special && unsigned.contains(['.', 'e', 'E']) => {
+            body.parse().map(Nbt::Double).map_err(|_| "invalid double")
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> Nbt {
+        let mut strings = NbtList::new();
+        strings.push("it's").unwrap();
+        strings.push("say \"hi\"\n").unwrap();
+
+        let mut lists = NbtList::new();
+        lists.push(NbtList::new()).unwrap();
+        lists
+            .push(NbtList::try_from(vec![Nbt::Short(-3)]).unwrap())
+            .unwrap();
+
+        NbtCompound::new()
+            .with("byte", -128i8)
+            .with("short", 32767i16)
+            .with("int", 0)
+            .with("long", i64::MAX)
+            .with("float", 0.1f32)
+            .with("double", -1.0e-300f64)
+            .with("nan", f32::NAN)
+            .with("infinity", f64::NEG_INFINITY)
+            .with("bytes", vec![-1i8, 2])
+            .with("ints", Vec::<i32>::new())
+            .with("longs", vec![i64::MIN])
+            .with("strings", strings)
+            .with("lists", lists)
+            .with("minecraft:key with space", NbtCompound::new())
+            .with("unicode", "\u{e9}\u{1F600}\u{0}")
+            .into()
+    }
+
+    /// NaN never equals itself, so compare the printed form instead.
+    fn assert_round_trip(nbt: &Nbt, snbt: &str) {
+        let parsed: Nbt = snbt.parse().expect("printed snbt should parse");
+        assert_eq!(parsed.to_string(), nbt.to_string());
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        let nbt = sample();
+        assert_round_trip(&nbt, &nbt.to_string());
+    }
+
+    #[test]
+    fn test_pretty_round_trip() {
+        let nbt = sample();
+        assert_round_trip(&nbt, &nbt.to_pretty_snbt());
+    }
+
+    #[test]
+    fn test_exact_values() {
+        let nbt: Nbt =
+            "{a: [B; 1b, -2B], b: [I;], c: [L; 5L], d: 1.5f, e: 2d, f: 3, g: true, h: text}"
+                .parse()
+                .unwrap();
+        let expected: Nbt = NbtCompound::new()
+            .with("a", vec![1i8, -2])
+            .with("b", Vec::<i32>::new())
+            .with("c", vec![5i64])
+            .with("d", 1.5f32)
+            .with("e", 2.0f64)
+            .with("f", 3)
+            .with("g", 1i8)
+            .with("h", "text")
+            .into();
+        assert_eq!(nbt, expected);
+        assert_eq!(
+            expected.to_string(),
+            r#"{a:[B;1b,-2b],b:[I;],c:[L;5L],d:1.5f,e:2.0d,f:3,g:1b,h:"text"}"#
+        );
+    }
+
+    #[test]
+    fn test_pretty() {
+        let nbt: Nbt = "{a:[1,2],b:{},c:[B;1b]}".parse().unwrap();
+        assert_eq!(
+            nbt.to_pretty_snbt(),
+            "{\n    a: [\n        1,\n        2\n    ],\n    b: {},\n    c: [B; 1b]\n}"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!("[1, 2b]".parse::<Nbt>().is_err());
+        assert!("{a: 1".parse::<Nbt>().is_err());
+        assert!("[B; 1, 2]".parse::<Nbt>().is_err());
+        assert!("300b".parse::<Nbt>().is_err());
+        assert!("{} trailing".parse::<Nbt>().is_err());
+        assert!("{: 1}".parse::<Nbt>().is_err());
+        assert!(format!("'{}'", "a".repeat(65536)).parse::<Nbt>().is_err());
+        assert!(
+            format!("{{{}: 1}}", "a".repeat(65536))
+                .parse::<Nbt>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_quoted_empty_key() {
+        let nbt: Nbt = r#"{"": 1}"#.parse().unwrap();
+        assert_eq!(nbt, NbtCompound::new().with("", 1).into());
+        assert_round_trip(&nbt, &nbt.to_string());
+    }
+}
@@ -0,0 +1,273 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use bytes::{Buf, BufMut};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::{Deserialize, Error, Limits, Serialize, datatype::TextComponent};
+
+/// Longest status JSON accepted, in characters.
+const MAX_LENGTH: usize = 32767;
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const DATA_URI_PREFIX: &str = "data:image/png;base64,";
+
+/// Server list entry sent in response to a status request, encoded as JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStatus {
+    pub version: StatusVersion,
+    pub players: Option<StatusPlayers>,
+    pub description: TextComponent,
+    pub favicon: Option<Favicon>,
+    pub enforces_secure_chat: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusVersion {
+    pub name: String,
+    pub protocol: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusPlayers {
+    pub max: i32,
+    pub online: i32,
+    pub sample: Vec<PlayerSample>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: Uuid,
+}
+
+/// A 64x64 PNG server icon, kept as the data URI the client expects. The data URI has to fit
+/// into the status JSON, so it is limited to [`MAX_LENGTH`] characters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Favicon(String);
+
+impl Favicon {
+    pub fn from_png(png: &[u8]) -> Result<Self, Error> {
+        // The signature is followed by the IHDR chunk: length, type, width and height.
+        if png.len() < 24 || png[..8] != PNG_SIGNATURE || &png[12..16] != b"IHDR" {
+            return Err(Error::InvalidFavicon("not a png image"));
+        }
+        let width = u32::from_be_bytes(png[16..20].try_into().expect("slice of four bytes"));
+        let height = u32::from_be_bytes(png[20..24].try_into().expect("slice of four bytes"));
+        if (width, height) != (64, 64) {
+            return Err(Error::InvalidFavicon("image must be 64x64 pixels"));
+        }
+
+        Self::from_data_uri(format!("{DATA_URI_PREFIX}{}", BASE64_STANDARD.encode(png)))
+    }
+
+    pub fn from_data_uri(uri: impl Into<String>) -> Result<Self, Error> {
+        let uri = uri.into();
+        if !uri.starts_with(DATA_URI_PREFIX) {
+            return Err(Error::InvalidFavicon("not a png data uri"));
+        }
+        if uri.chars().count() > MAX_LENGTH {
+            return Err(Error::InvalidFavicon(
+                "image exceeds the maximum status length",
+            ));
+        }
+        Ok(Self(uri))
+    }
+
+    pub fn as_data_uri(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ServerStatus {
+    pub fn to_json(&self) -> String {
+        self.encode_json(self.description.to_json_value())
+    }
+
+    /// Encodes the status with the description in the shape clients speaking `protocol` expect.
+    pub fn to_json_for(&self, protocol: i32) -> String {
+        self.encode_json(self.description.to_json_value_for(protocol))
+    }
+
+    fn encode_json(&self, description: Value) -> String {
+        let mut status = json!({
+            "version": {
+                "name": self.version.name,
+                "protocol": self.version.protocol,
+            },
+            "description": description,
+            "enforcesSecureChat": self.enforces_secure_chat,
+        });
+        if let Some(players) = &self.players {
+            let sample: Vec<Value> = players
+                .sample
+                .iter()
+                .map(|player| json!({ "name": player.name, "id": player.id.to_string() }))
+                .collect();
+            status["players"] = json!({
+                "max": players.max,
+                "online": players.online,
+                "sample": sample,
+            });
+        }
+        if let Some(favicon) = &self.favicon {
+            let without_favicon = status.to_string();
+            status["favicon"] = Value::String(favicon.0.clone());
+            let json = status.to_string();
+            if json.chars().count() > MAX_LENGTH {
+                tracing::warn!("omitting favicon that does not fit into the status");
+                return without_favicon;
+            }
+            return json;
+        }
+        status.to_string()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let status: Value = serde_json::from_str(json)?;
+
+        let string = |value: &Value| {
+            value
+                .as_str()
+                .map(str::to_string)
+                .ok_or(Error::InvalidStatus("expected string"))
+        };
+        let int = |value: &Value| {
+            value
+                .as_i64()
+                .map(|value| value as i32)
+                .ok_or(Error::InvalidStatus("expected number"))
+        };
+
+        let players = match status.get("players") {
+            None | Some(Value::Null) => None,
+            Some(players) => Some(StatusPlayers {
+                max: int(&players["max"])?,
+                online: int(&players["online"])?,
+                sample: match players.get("sample").and_then(Value::as_array) {
+                    None => vec![],
+                    Some(sample) => sample
+                        .iter()
+                        .map(|player| {
+                            Ok(PlayerSample {
+                                name: string(&player["name"])?,
+                                id: Uuid::parse_str(&string(&player["id"])?)
+                                    .map_err(|_| Error::InvalidStatus("invalid player id"))?,
+                            })
+                        })
+                        .collect::<Result<_, Error>>()?,
+                },
+            }),
+        };
+
+        Ok(Self {
+            version: StatusVersion {
+                name: string(&status["version"]["name"])?,
+                protocol: int(&status["version"]["protocol"])?,
+            },
+            players,
+            description: match status.get("description") {
+                None => TextComponent::text(""),
+                Some(description) => TextComponent::from_json_value(description)?,
+            },
+            favicon: status
+                .get("favicon")
+                .map(|favicon| Favicon::from_data_uri(string(favicon)?))
+                .transpose()?,
+            enforces_secure_chat: status
+                .get("enforcesSecureChat")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        })
+    }
+}
+
+impl Serialize for ServerStatus {
+    fn serialize<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        self.to_json().serialize(buf)
+    }
+}
+
+impl Deserialize for ServerStatus {
+    fn deserialize<B: Buf>(buf: &mut B, limits: &Limits) -> Result<Self, Error> {
+        let json = String::deserialize(buf, limits)?;
+        if json.chars().count() > MAX_LENGTH {
+            return Err(Error::InvalidStatus("json exceeds maximum length"));
+        }
+        Self::from_json(&json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(&13u32.to_be_bytes());
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&width.to_be_bytes());
+        png.extend_from_slice(&height.to_be_bytes());
+        png
+    }
+
+    #[test]
+    fn test_favicon_requires_64x64_png() {
+        let favicon = Favicon::from_png(&png(64, 64)).unwrap();
+        assert!(
+            favicon
+                .as_data_uri()
+                .starts_with("data:image/png;base64,iVBORw0KGgo")
+        );
+        assert!(Favicon::from_png(&png(32, 32)).is_err());
+        assert!(Favicon::from_png(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn test_favicon_length() {
+        let mut large = png(64, 64);
+        large.resize(MAX_LENGTH, 0);
+        assert!(matches!(
+            Favicon::from_png(&large),
+            Err(Error::InvalidFavicon(_))
+        ));
+
+        let favicon = format!("{DATA_URI_PREFIX}{}", "A".repeat(MAX_LENGTH - 40));
+        let status = ServerStatus {
+            version: StatusVersion {
+                name: "1.21.10".to_string(),
+                protocol: 773,
+            },
+            players: None,
+            description: TextComponent::text("Hello"),
+            favicon: Some(Favicon::from_data_uri(favicon).unwrap()),
+            enforces_secure_chat: false,
+        };
+        let json = status.to_json();
+        assert!(json.chars().count() <= MAX_LENGTH);
+        assert!(!json.contains("favicon"));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let status = ServerStatus {
+            version: StatusVersion {
+                name: "1.21.10".to_string(),
+                protocol: 773,
+            },
+            players: Some(StatusPlayers {
+                max: 100,
+                online: 1,
+                sample: vec![PlayerSample {
+                    name: "Notch".to_string(),
+                    id: Uuid::from_u128(0x069a79f444e94726a5befca90e38aaf5),
+                }],
+            }),
+            description: TextComponent::text("Hello").bold(true),
+            favicon: Some(Favicon::from_png(&png(64, 64)).unwrap()),
+            enforces_secure_chat: true,
+        };
+        let json = status.to_json();
+        assert!(json.contains(r#""enforcesSecureChat":true"#));
+        assert!(json.contains(r#""id":"069a79f4-44e9-4726-a5be-fca90e38aaf5""#));
+        assert_eq!(ServerStatus::from_json(&json).unwrap(), status);
+    }
+}
@@ -0,0 +1,227 @@
+//! Legacy formatting codes such as `§a` or `&l`, including the `§x§r§r§g§g§b§b` and `&#rrggbb`
+//! hex color extensions.
+
+use super::{Color, NamedColor, Style, TextComponent};
+
+/// Marker used by the vanilla client.
+pub const SECTION: char = '§';
+/// Marker commonly used in configuration files.
+pub const AMPERSAND: char = '&';
+
+impl TextComponent {
+    pub fn from_legacy(input: &str, marker: char) -> TextComponent {
+        let mut leaves = vec![];
+        let mut style = Style::default();
+        let mut text = String::new();
+        let mut rest = input;
+
+        while let Some(char) = rest.chars().next() {
+            let after = &rest[char.len_utf8()..];
+            if char != marker {
+                text.push(char);
+                rest = after;
+                continue;
+            }
+
+            let Some((next, consumed)) = parse_code(after, marker) else {
+                text.push(char);
+                rest = after;
+                continue;
+            };
+            rest = &after[consumed..];
+
+            leaves.push(TextComponent {
+                style: style.clone(),
+                ..TextComponent::text(std::mem::take(&mut text))
+            });
+            style = match next {
+                Code::Color(color) => Style {
+                    color: Some(color),
+                    ..Style::default()
+                },
+                Code::Reset => Style::default(),
+                Code::Format(apply) => {
+                    apply(&mut style);
+                    style
+                }
+            };
+        }
+        leaves.push(TextComponent {
+            style,
+            ..TextComponent::text(text)
+        });
+
+        TextComponent::from_leaves(leaves)
+    }
+
+    /// Replaces every hex color with the closest named one, for clients that predate hex colors.
+    pub fn with_named_colors(mut self) -> TextComponent {
+        if let Some(Color::Rgb(rgb)) = self.style.color {
+            self.style.color = Some(NamedColor::nearest(rgb).into());
+        }
+        self.extra = self
+            .extra
+            .into_iter()
+            .map(TextComponent::with_named_colors)
+            .collect();
+        self
+    }
+
+    /// Emits the component with legacy codes. Anything but colors and decorations is dropped.
+    pub fn to_legacy(&self, marker: char) -> String {
+        let mut output = String::new();
+        let mut current = Style::default();
+
+        for leaf in self.flatten() {
+            let style = legacy_style(&leaf.style);
+            let plain = leaf.to_plain();
+            if style != current {
+                match style.color {
+                    Some(color) => push_color(&mut output, marker, color),
+                    None if current != Style::default() => {
+                        output.push(marker);
+                        output.push('r');
+                    }
+                    None => {}
+                }
+                for (code, enabled) in [
+                    ('k', style.obfuscated),
+                    ('l', style.bold),
+                    ('m', style.strikethrough),
+                    ('n', style.underlined),
+                    ('o', style.italic),
+                ] {
+                    if enabled == Some(true) {
+                        output.push(marker);
+                        output.push(code);
+                    }
+                }
+                current = style;
+            }
+            output.push_str(&plain);
+        }
+
+        output
+    }
+}
+
+enum Code {
+    Color(Color),
+    Reset,
+    Format(fn(&mut Style)),
+}
+
+/// Parses the code following a marker, returning it with the number of bytes it spans.
+fn parse_code(input: &str, marker: char) -> Option<(Code, usize)> {
+    let code = input.chars().next()?.to_ascii_lowercase();
+    let code = match code {
+        'x' => {
+            let mut hex = String::new();
+            let mut chars = input[1..].chars();
+            for _ in 0..6 {
+                if chars.next()? != marker {
+                    return None;
+                }
+                hex.push(chars.next().filter(char::is_ascii_hexdigit)?);
+            }
+            let rgb = u32::from_str_radix(&hex, 16).ok()?;
+            return Some((
+                Code::Color(Color::Rgb(rgb)),
+                1 + 6 * (1 + marker.len_utf8()),
+            ));
+        }
+        '#' => {
+            let hex = input
+                .get(1..7)
+                .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))?;
+            let rgb = u32::from_str_radix(hex, 16).ok()?;
+            return Some((Code::Color(Color::Rgb(rgb)), 7));
+        }
+        'k' => Code::Format(|style| style.obfuscated = Some(true)),
+        'l' => Code::Format(|style| style.bold = Some(true)),
+        'm' => Code::Format(|style| style.strikethrough = Some(true)),
+        'n' => Code::Format(|style| style.underlined = Some(true)),
+        'o' => Code::Format(|style| style.italic = Some(true)),
+        'r' => Code::Reset,
+        code => {
+            let index = code.to_digit(16)?;
+            Code::Color(NamedColor::ALL[index as usize].into())
+        }
+    };
+    Some((code, 1))
+}
+
+fn legacy_style(style: &Style) -> Style {
+    let enabled = |value: Option<bool>| value.filter(|enabled| *enabled);
+    Style {
+        color: style.color,
+        bold: enabled(style.bold),
+        italic: enabled(style.italic),
+        underlined: enabled(style.underlined),
+        strikethrough: enabled(style.strikethrough),
+        obfuscated: enabled(style.obfuscated),
+        ..Style::default()
+    }
+}
+
+fn push_color(output: &mut String, marker: char, color: Color) {
+    match color {
+        Color::Named(color) => {
+            let index = NamedColor::ALL
+                .iter()
+                .position(|named| *named == color)
+                .expect("every named color has a code");
+            output.push(marker);
+            output.push(char::from_digit(index as u32, 16).expect("index below 16"));
+        }
+        Color::Rgb(rgb) => {
+            output.push(marker);
+            output.push('x');
+            for digit in format!("{rgb:06x}").chars() {
+                output.push(marker);
+                output.push(digit);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_colors_and_formats() {
+        let component = TextComponent::from_legacy("&aHello &l&#ff0000World&rtail", AMPERSAND);
+        assert_eq!(
+            component,
+            TextComponent::text("")
+                .append(TextComponent::text("Hello ").color(NamedColor::Green))
+                .append(TextComponent::text("World").color(Color::Rgb(0xFF0000)))
+                .append("tail")
+        );
+        assert_eq!(component.to_plain(), "Hello Worldtail");
+    }
+
+    #[test]
+    fn test_invalid_codes_stay_literal() {
+        assert_eq!(
+            TextComponent::from_legacy("Tom & Jerry &z", AMPERSAND),
+            TextComponent::text("Tom & Jerry &z")
+        );
+    }
+
+    #[test]
+    fn test_named_colors() {
+        let component = TextComponent::text("a")
+            .color(Color::Rgb(0xFE5050))
+            .append(TextComponent::text("b").color(Color::Rgb(0x0000A0)));
+        assert_eq!(component.with_named_colors().to_legacy(AMPERSAND), "&ca&1b");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = "§6§lGold §x§1§2§a§b§3§4hex§r plain";
+        let component = TextComponent::from_legacy(input, SECTION);
+        assert_eq!(component.to_legacy(SECTION), input);
+    }
+}
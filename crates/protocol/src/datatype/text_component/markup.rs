@@ -0,0 +1,525 @@
+//! Tag based markup in the style of MiniMessage, e.g. `<red><bold>Maintenance</bold></red>`.
+
+use super::{ClickEvent, Color, Content, HoverEvent, NamedColor, Style, TextComponent};
+
+impl TextComponent {
+    /// Parses tag markup. Unknown or malformed tags are kept as literal text.
+    pub fn from_markup(input: &str) -> TextComponent {
+        let mut leaves = vec![];
+        render(&Parser::parse(input), &Style::default(), &mut leaves);
+        TextComponent::from_leaves(leaves)
+    }
+
+    pub fn to_markup(&self) -> String {
+        let mut output = String::new();
+        for leaf in self.flatten() {
+            let tags = tags(&leaf.style);
+            for (open, _) in &tags {
+                output.push('<');
+                output.push_str(open);
+                output.push('>');
+            }
+            match &leaf.content {
+                Content::Text(text) => {
+                    for char in text.chars() {
+                        if matches!(char, '<' | '\\') {
+                            output.push('\\');
+                        }
+                        output.push(char);
+                    }
+                }
+                Content::Translatable { key, with, .. } => {
+                    output.push_str("<lang:");
+                    output.push_str(&quote(key));
+                    for argument in with {
+                        output.push(':');
+                        output.push_str(&quote(&argument.to_markup()));
+                    }
+                    output.push('>');
+                }
+                Content::Keybind(keybind) => {
+                    output.push_str(&format!("<key:{}>", quote(keybind)));
+                }
+                Content::Score { name, objective } => {
+                    output.push_str(&format!("<score:{}:{}>", quote(name), quote(objective)));
+                }
+            }
+            for (_, close) in tags.iter().rev() {
+                output.push_str("</");
+                output.push_str(close);
+                output.push('>');
+            }
+        }
+        output
+    }
+}
+
+enum Node {
+    Leaf(TextComponent),
+    Group { effect: Effect, children: Vec<Node> },
+}
+
+enum Effect {
+    Style(Style),
+    Gradient(Vec<Color>),
+}
+
+enum Tag {
+    Open(Effect),
+    Leaf(TextComponent),
+    Reset,
+}
+
+struct Group {
+    name: String,
+    effect: Effect,
+    children: Vec<Node>,
+}
+
+#[derive(Default)]
+struct Parser {
+    root: Vec<Node>,
+    stack: Vec<Group>,
+    text: String,
+}
+
+impl Parser {
+    fn parse(input: &str) -> Vec<Node> {
+        let mut parser = Parser::default();
+        let mut rest = input;
+
+        while let Some(char) = rest.chars().next() {
+            let after = &rest[char.len_utf8()..];
+            match char {
+                '\\' if after.starts_with(['<', '\\']) => {
+                    parser.text.push_str(&after[..1]);
+                    rest = &after[1..];
+                }
+                '<' => match split_tag(rest) {
+                    Some((tag, length)) if parser.tag(tag) => rest = &rest[length..],
+                    _ => {
+                        parser.text.push(char);
+                        rest = after;
+                    }
+                },
+                char => {
+                    parser.text.push(char);
+                    rest = after;
+                }
+            }
+        }
+
+        parser.flush();
+        while !parser.stack.is_empty() {
+            parser.close();
+        }
+        parser.root
+    }
+
+    /// Applies a tag, returning false if it is not understood.
+    fn tag(&mut self, tag: &str) -> bool {
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = canonical(name.split(':').next().unwrap_or_default());
+            let Some(position) = self.stack.iter().rposition(|group| group.name == name) else {
+                return false;
+            };
+            self.flush();
+            while self.stack.len() > position {
+                self.close();
+            }
+            return true;
+        }
+
+        let arguments = split_arguments(tag);
+        let (negated, name) = match arguments[0].strip_prefix('!') {
+            Some(name) => (true, canonical(name)),
+            None => (false, canonical(&arguments[0])),
+        };
+        let Some(tag) = parse_tag(&name, &arguments[1..], negated) else {
+            return false;
+        };
+
+        self.flush();
+        match tag {
+            Tag::Open(effect) => self.stack.push(Group {
+                name,
+                effect,
+                children: vec![],
+            }),
+            Tag::Leaf(component) => self.children().push(Node::Leaf(component)),
+            Tag::Reset => {
+                while !self.stack.is_empty() {
+                    self.close();
+                }
+            }
+        }
+        true
+    }
+
+    fn children(&mut self) -> &mut Vec<Node> {
+        match self.stack.last_mut() {
+            Some(group) => &mut group.children,
+            None => &mut self.root,
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            let text = std::mem::take(&mut self.text);
+            self.children().push(Node::Leaf(TextComponent::text(text)));
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(Group {
+            effect, children, ..
+        }) = self.stack.pop()
+        {
+            self.children().push(Node::Group { effect, children });
+        }
+    }
+}
+
+/// Finds the end of the tag starting at `input`, returning its inner text and total length.
+fn split_tag(input: &str) -> Option<(&str, usize)> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, char) in input.char_indices().skip(1) {
+        match (quote, char) {
+            _ if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(open), char) if char == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(char),
+            (None, '<') => return None,
+            (None, '>') if index > 1 => return Some((&input[1..index], index + 1)),
+            (None, '>') => return None,
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+fn split_arguments(tag: &str) -> Vec<String> {
+    let mut arguments = vec![String::new()];
+    let mut quote = None;
+    let mut chars = tag.chars();
+
+    while let Some(char) = chars.next() {
+        let argument = arguments.last_mut().expect("arguments are never empty");
+        match (quote, char) {
+            (Some(_), '\\') => argument.extend(chars.next()),
+            (Some(open), char) if char == open => quote = None,
+            (Some(_), char) => argument.push(char),
+            (None, '\'' | '"') => quote = Some(char),
+            (None, ':') => arguments.push(String::new()),
+            (None, char) => argument.push(char),
+        }
+    }
+    arguments
+}
+
+fn canonical(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    match name.as_str() {
+        "b" => "bold",
+        "i" | "em" => "italic",
+        "u" => "underlined",
+        "st" => "strikethrough",
+        "obf" => "obfuscated",
+        "c" | "colour" => "color",
+        "tr" | "translate" => "lang",
+        "br" => "newline",
+        _ => return name,
+    }
+    .to_string()
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    Color::parse(&value.to_ascii_lowercase().replace("grey", "gray"))
+}
+
+fn parse_tag(name: &str, arguments: &[String], negated: bool) -> Option<Tag> {
+    let style = |style: Style| Some(Tag::Open(Effect::Style(style)));
+    let decoration: Option<fn(&mut Style) -> &mut Option<bool>> = match name {
+        "bold" => Some(|style| &mut style.bold),
+        "italic" => Some(|style| &mut style.italic),
+        "underlined" => Some(|style| &mut style.underlined),
+        "strikethrough" => Some(|style| &mut style.strikethrough),
+        "obfuscated" => Some(|style| &mut style.obfuscated),
+        _ => None,
+    };
+    if let Some(field) = decoration
+        && arguments.is_empty()
+    {
+        let mut decorated = Style::default();
+        *field(&mut decorated) = Some(!negated);
+        return style(decorated);
+    }
+    if negated {
+        return None;
+    }
+
+    match (name, arguments) {
+        ("reset", []) => Some(Tag::Reset),
+        ("color", [color]) => style(Style {
+            color: Some(parse_color(color)?),
+            ..Style::default()
+        }),
+        ("gradient", colors) => {
+            let colors = match colors {
+                [] => vec![NamedColor::White.into(), NamedColor::Black.into()],
+                [_] => return None,
+                colors => colors
+                    .iter()
+                    .map(|color| parse_color(color))
+                    .collect::<Option<_>>()?,
+            };
+            Some(Tag::Open(Effect::Gradient(colors)))
+        }
+        ("font", [font]) => style(Style {
+            font: Some(font.clone()),
+            ..Style::default()
+        }),
+        ("insert", [_, ..]) => style(Style {
+            insertion: Some(arguments.join(":")),
+            ..Style::default()
+        }),
+        ("click", [action, value @ ..]) if !value.is_empty() => {
+            let value = value.join(":");
+            let event = match action.as_str() {
+                "open_url" => ClickEvent::OpenUrl(value),
+                "run_command" => ClickEvent::RunCommand(value),
+                "suggest_command" => ClickEvent::SuggestCommand(value),
+                "change_page" => ClickEvent::ChangePage(value.parse().ok()?),
+                "copy_to_clipboard" => ClickEvent::CopyToClipboard(value),
+                _ => return None,
+            };
+            style(Style {
+                click_event: Some(event),
+                ..Style::default()
+            })
+        }
+        ("hover", [action, value @ ..]) if action == "show_text" && !value.is_empty() => {
+            let text = TextComponent::from_markup(&value.join(":"));
+            style(Style {
+                hover_event: Some(HoverEvent::ShowText(Box::new(text))),
+                ..Style::default()
+            })
+        }
+        ("newline", []) => Some(Tag::Leaf(TextComponent::text("\n"))),
+        ("key", [keybind]) => Some(Tag::Leaf(TextComponent::keybind(keybind))),
+        ("lang", [key, with @ ..]) => Some(Tag::Leaf(TextComponent::translatable(
+            key,
+            with.iter()
+                .map(|argument| TextComponent::from_markup(argument))
+                .collect(),
+        ))),
+        ("score", [name, objective]) => Some(Tag::Leaf(TextComponent::score(name, objective))),
+        (color, []) => style(Style {
+            color: Some(parse_color(color)?),
+            ..Style::default()
+        }),
+        _ => None,
+    }
+}
+
+fn render(nodes: &[Node], style: &Style, leaves: &mut Vec<TextComponent>) {
+    for node in nodes {
+        match node {
+            Node::Leaf(component) => leaves.push(TextComponent {
+                style: component.style.inherit(style),
+                ..component.clone()
+            }),
+            Node::Group {
+                effect: Effect::Style(effect),
+                children,
+            } => render(children, &effect.inherit(style), leaves),
+            Node::Group {
+                effect: Effect::Gradient(colors),
+                children,
+            } => {
+                let mut inner = vec![];
+                render(children, style, &mut inner);
+                gradient(inner, colors, leaves);
+            }
+        }
+    }
+}
+
+/// Recolors every character of `inner` along the gradient.
+fn gradient(inner: Vec<TextComponent>, colors: &[Color], leaves: &mut Vec<TextComponent>) {
+    let units: usize = inner
+        .iter()
+        .map(|leaf| match &leaf.content {
+            Content::Text(text) => text.chars().count(),
+            _ => 1,
+        })
+        .sum();
+    let color_at = |index: usize| {
+        if units <= 1 {
+            return colors[0];
+        }
+        let position = index as f32 / (units - 1) as f32 * (colors.len() - 1) as f32;
+        let segment = (position.floor() as usize).min(colors.len() - 2);
+        colors[segment].lerp(colors[segment + 1], position - segment as f32)
+    };
+
+    let mut index = 0;
+    for leaf in inner {
+        let recolor = |style: &Style, index| Style {
+            color: Some(color_at(index)),
+            ..style.clone()
+        };
+        match &leaf.content {
+            Content::Text(text) => {
+                for char in text.chars() {
+                    leaves.push(TextComponent {
+                        style: recolor(&leaf.style, index),
+                        ..TextComponent::text(char)
+                    });
+                    index += 1;
+                }
+            }
+            _ => {
+                leaves.push(TextComponent {
+                    style: recolor(&leaf.style, index),
+                    ..leaf
+                });
+                index += 1;
+            }
+        }
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Opening and closing tag names that reproduce `style`.
+fn tags(style: &Style) -> Vec<(String, String)> {
+    let mut tags = vec![];
+    if let Some(color) = style.color {
+        let name = match color {
+            Color::Named(color) => color.name().to_string(),
+            Color::Rgb(rgb) => format!("#{rgb:06x}"),
+        };
+        tags.push((name.clone(), name));
+    }
+    for (name, value) in [
+        ("bold", style.bold),
+        ("italic", style.italic),
+        ("underlined", style.underlined),
+        ("strikethrough", style.strikethrough),
+        ("obfuscated", style.obfuscated),
+    ] {
+        match value {
+            Some(true) => tags.push((name.to_string(), name.to_string())),
+            Some(false) => tags.push((format!("!{name}"), name.to_string())),
+            None => {}
+        }
+    }
+    if let Some(font) = &style.font {
+        tags.push((format!("font:{}", quote(font)), "font".to_string()));
+    }
+    if let Some(insertion) = &style.insertion {
+        tags.push((format!("insert:{}", quote(insertion)), "insert".to_string()));
+    }
+    if let Some(event) = &style.click_event {
+        let (action, value) = match event {
+            ClickEvent::OpenUrl(url) => ("open_url", url.clone()),
+            ClickEvent::RunCommand(command) => ("run_command", command.clone()),
+            ClickEvent::SuggestCommand(command) => ("suggest_command", command.clone()),
+            ClickEvent::ChangePage(page) => ("change_page", page.to_string()),
+            ClickEvent::CopyToClipboard(value) => ("copy_to_clipboard", value.clone()),
+        };
+        tags.push((
+            format!("click:{action}:{}", quote(&value)),
+            "click".to_string(),
+        ));
+    }
+    if let Some(HoverEvent::ShowText(text)) = &style.hover_event {
+        tags.push((
+            format!("hover:show_text:{}", quote(&text.to_markup())),
+            "hover".to_string(),
+        ));
+    }
+    tags
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested_tags() {
+        let component = TextComponent::from_markup("<red><bold>Maintenance</bold> mode</red>!");
+        assert_eq!(
+            component,
+            TextComponent::text("")
+                .append(
+                    TextComponent::text("Maintenance")
+                        .color(NamedColor::Red)
+                        .bold(true)
+                )
+                .append(TextComponent::text(" mode").color(NamedColor::Red))
+                .append("!")
+        );
+        assert_eq!(component.to_plain(), "Maintenance mode!");
+    }
+
+    #[test]
+    fn test_unknown_tags_and_escapes_stay_literal() {
+        assert_eq!(
+            TextComponent::from_markup(r"<unknown>a \<red> 1 < 2"),
+            TextComponent::text("<unknown>a <red> 1 < 2")
+        );
+    }
+
+    #[test]
+    fn test_reset_and_negation() {
+        assert_eq!(
+            TextComponent::from_markup("<b>a<!b>b<reset>c"),
+            TextComponent::text("")
+                .append(TextComponent::text("a").bold(true))
+                .append(TextComponent::text("b").bold(false))
+                .append("c")
+        );
+    }
+
+    #[test]
+    fn test_gradient() {
+        let component = TextComponent::from_markup("<gradient:#000000:#ff0000>abc</gradient>");
+        let colors: Vec<_> = component
+            .flatten()
+            .into_iter()
+            .filter_map(|leaf| leaf.style.color)
+            .collect();
+        assert_eq!(
+            colors,
+            [
+                Color::Rgb(0x000000),
+                Color::Rgb(0x800000),
+                Color::Rgb(0xFF0000)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let component = TextComponent::text("Join ")
+            .color(Color::Rgb(0x12AB34))
+            .append(
+                TextComponent::text("here")
+                    .underlined(true)
+                    .click_event(ClickEvent::OpenUrl("https://example.com".to_string()))
+                    .show_text(TextComponent::text("it's <free>").italic(true)),
+            )
+            .append(TextComponent::keybind("key.jump"));
+        let markup = component.to_markup();
+        assert_eq!(
+            TextComponent::from_markup(&markup).flatten(),
+            component.flatten()
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use bytes::{Buf, BufMut};
+use uuid::Uuid;
+
+use crate::{Deserialize, Error, Limits, Serialize, datatype::Nbt};
+
+pub mod legacy;
+mod markup;
+mod style;
+mod value;
+
+pub use style::*;
+use value::EventFormat;
+
+/// A chat component. Encoded as NBT on the wire, use [`json`] for packets that still carry JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextComponent {
+    pub content: Content,
+    pub style: Style,
+    pub extra: Vec<TextComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Content {
+    Text(String),
+    Translatable {
+        key: String,
+        fallback: Option<String>,
+        with: Vec<TextComponent>,
+    },
+    Keybind(String),
+    Score {
+        name: String,
+        objective: String,
+    },
+}
+
+impl TextComponent {
+    pub fn new(content: Content) -> Self {
+        Self {
+            content,
+            style: Style::default(),
+            extra: vec![],
+        }
+    }
+
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(Content::Text(text.into()))
+    }
+
+    pub fn translatable(key: impl Into<String>, with: Vec<TextComponent>) -> Self {
+        Self::new(Content::Translatable {
+            key: key.into(),
+            fallback: None,
+            with,
+        })
+    }
+
+    pub fn keybind(keybind: impl Into<String>) -> Self {
+        Self::new(Content::Keybind(keybind.into()))
+    }
+
+    pub fn score(name: impl Into<String>, objective: impl Into<String>) -> Self {
+        Self::new(Content::Score {
+            name: name.into(),
+            objective: objective.into(),
+        })
+    }
+
+    /// Sets the fallback of a translatable component, ignored for other content types.
+    pub fn fallback(mut self, fallback: impl Into<String>) -> Self {
+        if let Content::Translatable { fallback: slot, .. } = &mut self.content {
+            *slot = Some(fallback.into());
+        }
+        self
+    }
+
+    pub fn color(mut self, color: impl Into<Color>) -> Self {
+        self.style.color = Some(color.into());
+        self
+    }
+
+    pub fn shadow_color(mut self, argb: i32) -> Self {
+        self.style.shadow_color = Some(argb);
+        self
+    }
+
+    pub fn font(mut self, font: impl Into<String>) -> Self {
+        self.style.font = Some(font.into());
+        self
+    }
+
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.style.bold = Some(bold);
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.style.italic = Some(italic);
+        self
+    }
+
+    pub fn underlined(mut self, underlined: bool) -> Self {
+        self.style.underlined = Some(underlined);
+        self
+    }
+
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.style.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn obfuscated(mut self, obfuscated: bool) -> Self {
+        self.style.obfuscated = Some(obfuscated);
+        self
+    }
+
+    pub fn insertion(mut self, insertion: impl Into<String>) -> Self {
+        self.style.insertion = Some(insertion.into());
+        self
+    }
+
+    pub fn click_event(mut self, event: ClickEvent) -> Self {
+        self.style.click_event = Some(event);
+        self
+    }
+
+    pub fn hover_event(mut self, event: HoverEvent) -> Self {
+        self.style.hover_event = Some(event);
+        self
+    }
+
+    pub fn show_text(self, text: impl Into<TextComponent>) -> Self {
+        self.hover_event(HoverEvent::ShowText(Box::new(text.into())))
+    }
+
+    pub fn show_entity(self, entity_type: impl Into<String>, uuid: Uuid) -> Self {
+        self.hover_event(HoverEvent::ShowEntity {
+            entity_type: entity_type.into(),
+            uuid,
+            name: None,
+        })
+    }
+
+    pub fn append(mut self, child: impl Into<TextComponent>) -> Self {
+        self.extra.push(child.into());
+        self
+    }
+
+    pub fn to_nbt(&self) -> Nbt {
+        value::encode(self, EventFormat::Inline).into()
+    }
+
+    pub fn from_nbt(nbt: &Nbt) -> Result<Self, Error> {
+        value::decode(&nbt.try_into()?)
+    }
+
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Self::from_json_value(&serde_json::from_str(json)?)
+    }
+
+    /// Encodes the component as JSON the way clients speaking `protocol` expect it. Hex colors
+    /// are replaced with named ones before 1.16 and events are keyed in camel case before 1.21.5.
+    pub fn to_json_for(&self, protocol: i32) -> String {
+        self.to_json_value_for(protocol).to_string()
+    }
+
+    pub(crate) fn to_json_value(&self) -> serde_json::Value {
+        value::encode(self, EventFormat::Inline).into()
+    }
+
+    pub(crate) fn to_json_value_for(&self, protocol: i32) -> serde_json::Value {
+        match EventFormat::for_protocol(protocol) {
+            format @ EventFormat::Value => {
+                value::encode(&self.clone().with_named_colors(), format).into()
+            }
+            format => value::encode(self, format).into(),
+        }
+    }
+
+    pub(crate) fn from_json_value(json: &serde_json::Value) -> Result<Self, Error> {
+        value::decode(&json.try_into()?)
+    }
+
+    /// Flattens the component to its text without any styling, used for logs.
+    pub fn to_plain(&self) -> String {
+        let mut plain = String::new();
+        for leaf in self.flatten() {
+            match leaf.content {
+                Content::Text(text) => plain.push_str(&text),
+                Content::Translatable { key, fallback, .. } => {
+                    plain.push_str(fallback.as_deref().unwrap_or(&key))
+                }
+                Content::Keybind(keybind) => plain.push_str(&keybind),
+                Content::Score { name, .. } => plain.push_str(&name),
+            }
+        }
+        plain
+    }
+
+    /// Resolves the tree into a list of childless components with fully inherited styles,
+    /// leaving out empty text.
+    pub(crate) fn flatten(&self) -> Vec<TextComponent> {
+        let mut leaves = vec![];
+        self.flatten_into(&Style::default(), &mut leaves);
+        leaves
+    }
+
+    fn flatten_into(&self, parent: &Style, leaves: &mut Vec<TextComponent>) {
+        let style = self.style.inherit(parent);
+        if !matches!(&self.content, Content::Text(text) if text.is_empty()) {
+            leaves.push(TextComponent {
+                content: self.content.clone(),
+                style: style.clone(),
+                extra: vec![],
+            });
+        }
+        for child in &self.extra {
+            child.flatten_into(&style, leaves);
+        }
+    }
+
+    /// Builds a component from a list of childless components, merging neighbouring text with
+    /// equal styles.
+    pub(crate) fn from_leaves(leaves: Vec<TextComponent>) -> TextComponent {
+        let mut merged: Vec<TextComponent> = vec![];
+        for leaf in leaves {
+            if let Content::Text(text) = &leaf.content {
+                if text.is_empty() {
+                    continue;
+                }
+                if let Some(TextComponent {
+                    content: Content::Text(previous),
+                    style,
+                    ..
+                }) = merged.last_mut()
+                    && *style == leaf.style
+                {
+                    previous.push_str(text);
+                    continue;
+                }
+            }
+            merged.push(leaf);
+        }
+
+        match merged.len() {
+            0 => TextComponent::text(""),
+            1 => merged.remove(0),
+            _ => TextComponent {
+                extra: merged,
+                ..TextComponent::text("")
+            },
+        }
+    }
+}
+
+impl From<&str> for TextComponent {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<String> for TextComponent {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl Serialize for TextComponent {
+    fn serialize<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        self.to_nbt().serialize(buf)
+    }
+}
+
+impl Deserialize for TextComponent {
+    fn deserialize<B: Buf>(buf: &mut B, limits: &Limits) -> Result<Self, Error> {
+        Self::from_nbt(&Nbt::read(buf, limits.nbt)?)
+    }
+}
+
+/// Components encoded as a JSON string, for use with `#[protocol(with = text_component::json)]`.
+pub mod json {
+    use bytes::{Buf, BufMut};
+
+    use super::TextComponent;
+    use crate::{Deserialize, Error, Limits, Serialize};
+
+    /// Longest JSON string accepted, in bytes.
+    pub const MAX_LENGTH: usize = 262144;
+
+    pub fn deserialize<B: Buf>(buf: &mut B, limits: &Limits) -> Result<TextComponent, Error> {
+        let json = String::deserialize(buf, limits)?;
+        if json.len() > MAX_LENGTH {
+            return Err(Error::InvalidTextComponent("json exceeds maximum length"));
+        }
+        TextComponent::from_json(&json)
+    }
+
+    pub fn serialize<B: BufMut>(value: &TextComponent, buf: &mut B) -> Result<(), Error> {
+        value.to_json().serialize(buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use super::*;
+
+    fn sample() -> TextComponent {
+        TextComponent::text("Hello ")
+            .color(NamedColor::Gold)
+            .bold(true)
+            .click_event(ClickEvent::OpenUrl("https://example.com".to_string()))
+            .show_text(TextComponent::text("tooltip").italic(false))
+            .append(TextComponent::translatable(
+                "chat.type.text",
+                vec!["a".into(), TextComponent::keybind("key.jump")],
+            ))
+            .append(TextComponent::score("@p", "kills").color(Color::Rgb(0x12AB34)))
+            .append(TextComponent::text("entity").show_entity("minecraft:pig", Uuid::from_u128(42)))
+    }
+
+    #[test]
+    fn test_plain_text_is_a_bare_string() {
+        assert_eq!(TextComponent::text("hi").to_json(), r#""hi""#);
+        assert_eq!(
+            TextComponent::text("hi").to_nbt(),
+            Nbt::String("hi".to_string())
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let component = sample();
+        let json = component.to_json();
+        assert!(json.contains(r#""color":"gold""#));
+        assert_eq!(TextComponent::from_json(&json).unwrap(), component);
+    }
+
+    #[test]
+    fn test_json_for_protocol() {
+        let json = sample().to_json_for(773);
+        assert_eq!(json, sample().to_json());
+
+        let json = sample().to_json_for(767);
+        assert!(
+            json.contains(r#""clickEvent":{"action":"open_url","value":"https://example.com"}"#)
+        );
+        assert!(json.contains(r#""hoverEvent":{"action":"show_text","contents":"#));
+        assert!(json.contains(r##""color":"#12AB34""##));
+        assert!(!json.contains("click_event"));
+
+        let json = sample().to_json_for(47);
+        assert!(json.contains(r#""hoverEvent":{"action":"show_text","value":"#));
+        assert!(json.contains(r#"\"minecraft:pig\""#));
+        assert!(!json.contains("#12AB34"));
+    }
+
+    #[test]
+    fn test_nbt_round_trip() {
+        let component = sample();
+        let mut buf = BytesMut::new();
+        component.serialize(&mut buf).unwrap();
+        assert_eq!(
+            TextComponent::deserialize(&mut buf, &Limits::DEFAULT).unwrap(),
+            component
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_nbt_string_too_long() {
+        let component = TextComponent::text("a".repeat(u16::MAX as usize + 1));
+        assert!(matches!(
+            component.serialize(&mut BytesMut::new()),
+            Err(Error::StringTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_lists_and_wrapped_elements() {
+        let component = TextComponent::from_json(r#"["a", {"text": "b"}, "c"]"#).unwrap();
+        assert_eq!(component, TextComponent::text("a").append("b").append("c"));
+
+        let nbt: Nbt = r#"{text: "a", extra: [{"": "b"}]}"#.parse().unwrap();
+        assert_eq!(
+            TextComponent::from_nbt(&nbt).unwrap(),
+            TextComponent::text("a").append("b")
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use uuid::Uuid;
+
+use super::TextComponent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NamedColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+}
+
+impl NamedColor {
+    pub const ALL: [NamedColor; 16] = [
+        NamedColor::Black,
+        NamedColor::DarkBlue,
+        NamedColor::DarkGreen,
+        NamedColor::DarkAqua,
+        NamedColor::DarkRed,
+        NamedColor::DarkPurple,
+        NamedColor::Gold,
+        NamedColor::Gray,
+        NamedColor::DarkGray,
+        NamedColor::Blue,
+        NamedColor::Green,
+        NamedColor::Aqua,
+        NamedColor::Red,
+        NamedColor::LightPurple,
+        NamedColor::Yellow,
+        NamedColor::White,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedColor::Black => "black",
+            NamedColor::DarkBlue => "dark_blue",
+            NamedColor::DarkGreen => "dark_green",
+            NamedColor::DarkAqua => "dark_aqua",
+            NamedColor::DarkRed => "dark_red",
+            NamedColor::DarkPurple => "dark_purple",
+            NamedColor::Gold => "gold",
+            NamedColor::Gray => "gray",
+            NamedColor::DarkGray => "dark_gray",
+            NamedColor::Blue => "blue",
+            NamedColor::Green => "green",
+            NamedColor::Aqua => "aqua",
+            NamedColor::Red => "red",
+            NamedColor::LightPurple => "light_purple",
+            NamedColor::Yellow => "yellow",
+            NamedColor::White => "white",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|color| color.name() == name)
+    }
+
+    /// The named color closest to `rgb`.
+    pub fn nearest(rgb: u32) -> Self {
+        let distance = |color: &NamedColor| {
+            let other = color.rgb();
+            [16, 8, 0]
+                .into_iter()
+                .map(|shift| {
+                    let delta = (rgb >> shift & 0xFF) as i32 - (other >> shift & 0xFF) as i32;
+                    delta * delta
+                })
+                .sum::<i32>()
+        };
+        Self::ALL
+            .into_iter()
+            .min_by_key(distance)
+            .expect("there are named colors")
+    }
+
+    pub fn rgb(self) -> u32 {
+        match self {
+            NamedColor::Black => 0x000000,
+            NamedColor::DarkBlue => 0x0000AA,
+            NamedColor::DarkGreen => 0x00AA00,
+            NamedColor::DarkAqua => 0x00AAAA,
+            NamedColor::DarkRed => 0xAA0000,
+            NamedColor::DarkPurple => 0xAA00AA,
+            NamedColor::Gold => 0xFFAA00,
+            NamedColor::Gray => 0xAAAAAA,
+            NamedColor::DarkGray => 0x555555,
+            NamedColor::Blue => 0x5555FF,
+            NamedColor::Green => 0x55FF55,
+            NamedColor::Aqua => 0x55FFFF,
+            NamedColor::Red => 0xFF5555,
+            NamedColor::LightPurple => 0xFF55FF,
+            NamedColor::Yellow => 0xFFFF55,
+            NamedColor::White => 0xFFFFFF,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Named(NamedColor),
+    Rgb(u32),
+}
+
+impl Color {
+    pub fn rgb(self) -> u32 {
+        match self {
+            Color::Named(color) => color.rgb(),
+            Color::Rgb(rgb) => rgb,
+        }
+    }
+
+    /// Parses a color name or a `#RRGGBB` hex color.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 => u32::from_str_radix(hex, 16).ok().map(Color::Rgb),
+            Some(_) => None,
+            None => NamedColor::from_name(value).map(Color::Named),
+        }
+    }
+
+    /// Linear interpolation between two colors, `t` ranging from 0 to 1.
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let channel = |shift: u32| {
+            let from = (self.rgb() >> shift & 0xFF) as f32;
+            let to = (other.rgb() >> shift & 0xFF) as f32;
+            ((from + (to - from) * t).round() as u32) << shift
+        };
+        Color::Rgb(channel(16) | channel(8) | channel(0))
+    }
+
+    pub fn to_name(self) -> String {
+        match self {
+            Color::Named(color) => color.name().to_string(),
+            Color::Rgb(rgb) => format!("#{rgb:06X}"),
+        }
+    }
+}
+
+impl From<NamedColor> for Color {
+    fn from(color: NamedColor) -> Self {
+        Color::Named(color)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClickEvent {
+    OpenUrl(String),
+    RunCommand(String),
+    SuggestCommand(String),
+    ChangePage(i32),
+    CopyToClipboard(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HoverEvent {
+    ShowText(Box<TextComponent>),
+    ShowItem {
+        id: String,
+        count: i32,
+    },
+    ShowEntity {
+        entity_type: String,
+        uuid: Uuid,
+        name: Option<Box<TextComponent>>,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Style {
+    pub color: Option<Color>,
+    /// ARGB color of the text shadow.
+    pub shadow_color: Option<i32>,
+    pub font: Option<String>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    pub insertion: Option<String>,
+    pub click_event: Option<ClickEvent>,
+    pub hover_event: Option<HoverEvent>,
+}
+
+impl Style {
+    pub fn is_empty(&self) -> bool {
+        *self == Style::default()
+    }
+
+    /// Fills every unset property from `parent`, the way the client resolves child styles.
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            color: self.color.or(parent.color),
+            shadow_color: self.shadow_color.or(parent.shadow_color),
+            font: self.font.clone().or_else(|| parent.font.clone()),
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underlined: self.underlined.or(parent.underlined),
+            strikethrough: self.strikethrough.or(parent.strikethrough),
+            obfuscated: self.obfuscated.or(parent.obfuscated),
+            insertion: self.insertion.clone().or_else(|| parent.insertion.clone()),
+            click_event: self
+                .click_event
+                .clone()
+                .or_else(|| parent.click_event.clone()),
+            hover_event: self
+                .hover_event
+                .clone()
+                .or_else(|| parent.hover_event.clone()),
+        }
+    }
+}
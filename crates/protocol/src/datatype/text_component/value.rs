@@ -0,0 +1,577 @@
+//! Encoding independent form of a component, shared by the JSON and the NBT representation.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Number, Value as Json};
+use uuid::Uuid;
+
+use super::{ClickEvent, Color, Content, HoverEvent, Style, TextComponent};
+use crate::{
+    Error,
+    datatype::{Nbt, NbtCompound, NbtList},
+};
+
+const INVALID: Error = Error::InvalidTextComponent("unsupported value");
+
+/// Shape of click and hover events, which changed between protocol versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum EventFormat {
+    /// Before 1.16, hover events carry a `value`, which is SNBT for items and entities.
+    Value,
+    /// Until 1.21.5, events are keyed in camel case and hover events carry `contents`.
+    Contents,
+    /// Events are keyed in snake case and their fields are inlined.
+    Inline,
+}
+
+impl EventFormat {
+    pub(super) fn for_protocol(protocol: i32) -> Self {
+        match protocol {
+            ..735 => EventFormat::Value,
+            735..770 => EventFormat::Contents,
+            _ => EventFormat::Inline,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Value {
+    Bool(bool),
+    Int(i64),
+    String(String),
+    IntArray(Vec<i32>),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    fn as_str(&self) -> Result<&str, Error> {
+        match self {
+            Value::String(value) => Ok(value),
+            _ => Err(Error::InvalidTextComponent("expected string")),
+        }
+    }
+
+    fn as_bool(&self) -> Result<bool, Error> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            Value::Int(value) => Ok(*value != 0),
+            _ => Err(Error::InvalidTextComponent("expected boolean")),
+        }
+    }
+
+    fn as_int(&self) -> Result<i32, Error> {
+        match self {
+            Value::Int(value) => Ok(*value as i32),
+            _ => Err(Error::InvalidTextComponent("expected number")),
+        }
+    }
+
+    fn as_uuid(&self) -> Result<Uuid, Error> {
+        let ints = match self {
+            Value::String(value) => {
+                return Uuid::parse_str(value)
+                    .map_err(|_| Error::InvalidTextComponent("invalid uuid"));
+            }
+            Value::IntArray(ints) => ints.clone(),
+            Value::List(values) => values
+                .iter()
+                .map(Value::as_int)
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(Error::InvalidTextComponent("expected uuid")),
+        };
+        let [a, b, c, d] = ints[..] else {
+            return Err(Error::InvalidTextComponent("uuid must have four ints"));
+        };
+        let high = (a as u32 as u64) << 32 | b as u32 as u64;
+        let low = (c as u32 as u64) << 32 | d as u32 as u64;
+        Ok(Uuid::from_u64_pair(high, low))
+    }
+
+    fn uuid(uuid: &Uuid) -> Value {
+        let (high, low) = uuid.as_u64_pair();
+        Value::IntArray(vec![
+            (high >> 32) as i32,
+            high as i32,
+            (low >> 32) as i32,
+            low as i32,
+        ])
+    }
+}
+
+pub(super) fn encode(component: &TextComponent, format: EventFormat) -> Value {
+    if let Content::Text(text) = &component.content
+        && component.style.is_empty()
+        && component.extra.is_empty()
+    {
+        return Value::String(text.clone());
+    }
+
+    let mut map = BTreeMap::new();
+    match &component.content {
+        Content::Text(text) => {
+            map.insert("text".to_string(), Value::String(text.clone()));
+        }
+        Content::Translatable {
+            key,
+            fallback,
+            with,
+        } => {
+            map.insert("translate".to_string(), Value::String(key.clone()));
+            if let Some(fallback) = fallback {
+                map.insert("fallback".to_string(), Value::String(fallback.clone()));
+            }
+            if !with.is_empty() {
+                map.insert("with".to_string(), encode_list(with, format));
+            }
+        }
+        Content::Keybind(keybind) => {
+            map.insert("keybind".to_string(), Value::String(keybind.clone()));
+        }
+        Content::Score { name, objective } => {
+            map.insert(
+                "score".to_string(),
+                Value::Map(BTreeMap::from([
+                    ("name".to_string(), Value::String(name.clone())),
+                    ("objective".to_string(), Value::String(objective.clone())),
+                ])),
+            );
+        }
+    }
+    encode_style(&component.style, format, &mut map);
+    if !component.extra.is_empty() {
+        map.insert("extra".to_string(), encode_list(&component.extra, format));
+    }
+    Value::Map(map)
+}
+
+/// Children are always written as maps so that NBT lists stay homogeneous.
+fn encode_list(components: &[TextComponent], format: EventFormat) -> Value {
+    Value::List(
+        components
+            .iter()
+            .map(|component| match encode(component, format) {
+                Value::String(text) => {
+                    Value::Map(BTreeMap::from([("text".to_string(), Value::String(text))]))
+                }
+                value => value,
+            })
+            .collect(),
+    )
+}
+
+fn encode_style(style: &Style, format: EventFormat, map: &mut BTreeMap<String, Value>) {
+    let mut insert = |key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            map.insert(key.to_string(), value);
+        }
+    };
+
+    insert(
+        "color",
+        style.color.map(|color| Value::String(color.to_name())),
+    );
+    insert(
+        "shadow_color",
+        style.shadow_color.map(|color| Value::Int(color as i64)),
+    );
+    insert("font", style.font.clone().map(Value::String));
+    insert("bold", style.bold.map(Value::Bool));
+    insert("italic", style.italic.map(Value::Bool));
+    insert("underlined", style.underlined.map(Value::Bool));
+    insert("strikethrough", style.strikethrough.map(Value::Bool));
+    insert("obfuscated", style.obfuscated.map(Value::Bool));
+    insert("insertion", style.insertion.clone().map(Value::String));
+    let (click_key, hover_key) = match format {
+        EventFormat::Inline => ("click_event", "hover_event"),
+        EventFormat::Value | EventFormat::Contents => ("clickEvent", "hoverEvent"),
+    };
+    insert(
+        click_key,
+        style
+            .click_event
+            .as_ref()
+            .map(|event| encode_click_event(event, format)),
+    );
+    insert(
+        hover_key,
+        style
+            .hover_event
+            .as_ref()
+            .map(|event| encode_hover_event(event, format)),
+    );
+}
+
+fn encode_click_event(event: &ClickEvent, format: EventFormat) -> Value {
+    let (action, key, value) = match event {
+        ClickEvent::OpenUrl(url) => ("open_url", "url", Value::String(url.clone())),
+        ClickEvent::RunCommand(command) => {
+            ("run_command", "command", Value::String(command.clone()))
+        }
+        ClickEvent::SuggestCommand(command) => {
+            ("suggest_command", "command", Value::String(command.clone()))
+        }
+        ClickEvent::ChangePage(page) => match format {
+            EventFormat::Inline => ("change_page", "page", Value::Int(*page as i64)),
+            EventFormat::Value | EventFormat::Contents => {
+                ("change_page", "page", Value::String(page.to_string()))
+            }
+        },
+        ClickEvent::CopyToClipboard(value) => {
+            ("copy_to_clipboard", "value", Value::String(value.clone()))
+        }
+    };
+    // Older clients read the argument of every action from `value`.
+    let key = match format {
+        EventFormat::Inline => key,
+        EventFormat::Value | EventFormat::Contents => "value",
+    };
+    Value::Map(BTreeMap::from([
+        ("action".to_string(), Value::String(action.to_string())),
+        (key.to_string(), value),
+    ]))
+}
+
+fn encode_hover_event(event: &HoverEvent, format: EventFormat) -> Value {
+    let mut map = BTreeMap::new();
+    let action = match event {
+        HoverEvent::ShowText(_) => "show_text",
+        HoverEvent::ShowItem { .. } => "show_item",
+        HoverEvent::ShowEntity { .. } => "show_entity",
+    };
+    map.insert("action".to_string(), Value::String(action.to_string()));
+
+    match format {
+        EventFormat::Inline => match event {
+            HoverEvent::ShowText(text) => {
+                map.insert("value".to_string(), encode(text, format));
+            }
+            HoverEvent::ShowItem { id, count } => {
+                map.insert("id".to_string(), Value::String(id.clone()));
+                map.insert("count".to_string(), Value::Int(*count as i64));
+            }
+            HoverEvent::ShowEntity {
+                entity_type,
+                uuid,
+                name,
+            } => {
+                map.insert("id".to_string(), Value::String(entity_type.clone()));
+                map.insert("uuid".to_string(), Value::uuid(uuid));
+                if let Some(name) = name {
+                    map.insert("name".to_string(), encode(name, format));
+                }
+            }
+        },
+        EventFormat::Contents => {
+            let contents = match event {
+                HoverEvent::ShowText(text) => encode(text, format),
+                HoverEvent::ShowItem { id, count } => Value::Map(BTreeMap::from([
+                    ("id".to_string(), Value::String(id.clone())),
+                    ("count".to_string(), Value::Int(*count as i64)),
+                ])),
+                HoverEvent::ShowEntity {
+                    entity_type,
+                    uuid,
+                    name,
+                } => {
+                    let mut contents = BTreeMap::from([
+                        ("type".to_string(), Value::String(entity_type.clone())),
+                        ("id".to_string(), Value::String(uuid.to_string())),
+                    ]);
+                    if let Some(name) = name {
+                        contents.insert("name".to_string(), encode(name, format));
+                    }
+                    Value::Map(contents)
+                }
+            };
+            map.insert("contents".to_string(), contents);
+        }
+        EventFormat::Value => {
+            let value = match event {
+                HoverEvent::ShowText(text) => encode(text, format),
+                HoverEvent::ShowItem { id, count } => Value::String(
+                    Nbt::from(
+                        NbtCompound::new()
+                            .with("id", id.as_str())
+                            .with("Count", *count as i8),
+                    )
+                    .to_string(),
+                ),
+                HoverEvent::ShowEntity {
+                    entity_type,
+                    uuid,
+                    name,
+                } => {
+                    let mut entity = NbtCompound::new()
+                        .with("type", entity_type.as_str())
+                        .with("id", uuid.to_string());
+                    if let Some(name) = name {
+                        let name: Json = encode(name, format).into();
+                        entity = entity.with("name", name.to_string());
+                    }
+                    Value::String(Nbt::from(entity).to_string())
+                }
+            };
+            map.insert("value".to_string(), value);
+        }
+    }
+    Value::Map(map)
+}
+
+pub(super) fn decode(value: &Value) -> Result<TextComponent, Error> {
+    let map = match value {
+        Value::String(text) => return Ok(TextComponent::text(text.clone())),
+        Value::Bool(value) => return Ok(TextComponent::text(value.to_string())),
+        Value::Int(value) => return Ok(TextComponent::text(value.to_string())),
+        Value::List(values) => {
+            let (first, rest) = values
+                .split_first()
+                .ok_or(Error::InvalidTextComponent("empty component list"))?;
+            let mut component = decode(first)?;
+            for value in rest {
+                component.extra.push(decode(value)?);
+            }
+            return Ok(component);
+        }
+        Value::IntArray(_) => return Err(INVALID),
+        Value::Map(map) => map,
+    };
+
+    // Lists that mix types are stored in NBT as compounds wrapping the element under "".
+    if map.len() == 1
+        && let Some(value) = map.get("")
+    {
+        return decode(value);
+    }
+
+    let content = if let Some(text) = map.get("text") {
+        Content::Text(text.as_str()?.to_string())
+    } else if let Some(key) = map.get("translate") {
+        Content::Translatable {
+            key: key.as_str()?.to_string(),
+            fallback: map
+                .get("fallback")
+                .map(|fallback| fallback.as_str().map(str::to_string))
+                .transpose()?,
+            with: decode_list(map.get("with"))?,
+        }
+    } else if let Some(keybind) = map.get("keybind") {
+        Content::Keybind(keybind.as_str()?.to_string())
+    } else if let Some(score) = map.get("score") {
+        let Value::Map(score) = score else {
+            return Err(Error::InvalidTextComponent("expected score object"));
+        };
+        let field = |key| {
+            score
+                .get(key)
+                .ok_or(Error::InvalidTextComponent("incomplete score"))?
+                .as_str()
+                .map(str::to_string)
+        };
+        Content::Score {
+            name: field("name")?,
+            objective: field("objective")?,
+        }
+    } else {
+        return Err(Error::InvalidTextComponent("unknown content type"));
+    };
+
+    Ok(TextComponent {
+        content,
+        style: decode_style(map)?,
+        extra: decode_list(map.get("extra"))?,
+    })
+}
+
+fn decode_list(value: Option<&Value>) -> Result<Vec<TextComponent>, Error> {
+    match value {
+        None => Ok(vec![]),
+        Some(Value::List(values)) => values.iter().map(decode).collect(),
+        Some(_) => Err(Error::InvalidTextComponent("expected list")),
+    }
+}
+
+fn decode_style(map: &BTreeMap<String, Value>) -> Result<Style, Error> {
+    let string = |key| {
+        map.get(key)
+            .map(|value| value.as_str().map(str::to_string))
+            .transpose()
+    };
+    let bool = |key| map.get(key).map(Value::as_bool).transpose();
+
+    let click_event = match map.get("click_event") {
+        None => None,
+        Some(Value::Map(event)) => {
+            let field = |key| {
+                event
+                    .get(key)
+                    .ok_or(Error::InvalidTextComponent("incomplete click event"))
+            };
+            let text = |key| field(key)?.as_str().map(str::to_string);
+            Some(match field("action")?.as_str()? {
+                "open_url" => ClickEvent::OpenUrl(text("url")?),
+                "run_command" => ClickEvent::RunCommand(text("command")?),
+                "suggest_command" => ClickEvent::SuggestCommand(text("command")?),
+                "change_page" => ClickEvent::ChangePage(field("page")?.as_int()?),
+                "copy_to_clipboard" => ClickEvent::CopyToClipboard(text("value")?),
+                _ => return Err(Error::InvalidTextComponent("unknown click event")),
+            })
+        }
+        Some(_) => return Err(Error::InvalidTextComponent("expected click event object")),
+    };
+
+    let hover_event = match map.get("hover_event") {
+        None => None,
+        Some(Value::Map(event)) => {
+            let field = |key| {
+                event
+                    .get(key)
+                    .ok_or(Error::InvalidTextComponent("incomplete hover event"))
+            };
+            Some(match field("action")?.as_str()? {
+                "show_text" => HoverEvent::ShowText(Box::new(decode(field("value")?)?)),
+                "show_item" => HoverEvent::ShowItem {
+                    id: field("id")?.as_str()?.to_string(),
+                    count: event.get("count").map_or(Ok(1), Value::as_int)?,
+                },
+                "show_entity" => HoverEvent::ShowEntity {
+                    entity_type: field("id")?.as_str()?.to_string(),
+                    uuid: field("uuid")?.as_uuid()?,
+                    name: event
+                        .get("name")
+                        .map(|name| decode(name).map(Box::new))
+                        .transpose()?,
+                },
+                _ => return Err(Error::InvalidTextComponent("unknown hover event")),
+            })
+        }
+        Some(_) => return Err(Error::InvalidTextComponent("expected hover event object")),
+    };
+
+    Ok(Style {
+        color: string("color")?
+            .map(|color| Color::parse(&color).ok_or(Error::InvalidTextComponent("invalid color")))
+            .transpose()?,
+        shadow_color: map.get("shadow_color").map(Value::as_int).transpose()?,
+        font: string("font")?,
+        bold: bool("bold")?,
+        italic: bool("italic")?,
+        underlined: bool("underlined")?,
+        strikethrough: bool("strikethrough")?,
+        obfuscated: bool("obfuscated")?,
+        insertion: string("insertion")?,
+        click_event,
+        hover_event,
+    })
+}
+
+impl From<Value> for Nbt {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Bool(value) => Nbt::Byte(value as i8),
+            Value::Int(value) => Nbt::Int(value as i32),
+            Value::String(value) => Nbt::String(value),
+            Value::IntArray(values) => Nbt::IntArray(values),
+            Value::List(values) => {
+                let items: Vec<Nbt> = values.into_iter().map(Nbt::from).collect();
+                let homogeneous = items
+                    .windows(2)
+                    .all(|pair| pair[0].tag_type() == pair[1].tag_type());
+
+                let mut list = NbtList::new();
+                for item in items {
+                    let item = if homogeneous {
+                        item
+                    } else {
+                        NbtCompound::new().with("", item).into()
+                    };
+                    list.push(item).expect("list elements share one type");
+                }
+                Nbt::List(list)
+            }
+            Value::Map(map) => {
+                Nbt::Compound(map.into_iter().map(|(k, v)| (k, Nbt::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl TryFrom<&Nbt> for Value {
+    type Error = Error;
+
+    fn try_from(nbt: &Nbt) -> Result<Self, Self::Error> {
+        Ok(match nbt {
+            Nbt::Byte(value) => Value::Int(*value as i64),
+            Nbt::Short(value) => Value::Int(*value as i64),
+            Nbt::Int(value) => Value::Int(*value as i64),
+            Nbt::Long(value) => Value::Int(*value),
+            Nbt::String(value) => Value::String(value.clone()),
+            Nbt::IntArray(values) => Value::IntArray(values.clone()),
+            Nbt::List(list) => {
+                Value::List(list.iter().map(Value::try_from).collect::<Result<_, _>>()?)
+            }
+            Nbt::Compound(compound) => Value::Map(
+                compound
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), Value::try_from(value)?)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Nbt::Float(_) | Nbt::Double(_) | Nbt::ByteArray(_) | Nbt::LongArray(_) => {
+                return Err(INVALID);
+            }
+        })
+    }
+}
+
+impl From<Value> for Json {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Bool(value) => Json::Bool(value),
+            Value::Int(value) => Json::Number(value.into()),
+            Value::String(value) => Json::String(value),
+            Value::IntArray(values) => Json::Array(
+                values
+                    .into_iter()
+                    .map(|value| Json::Number(value.into()))
+                    .collect(),
+            ),
+            Value::List(values) => Json::Array(values.into_iter().map(Json::from).collect()),
+            Value::Map(map) => Json::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, Json::from(value)))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+}
+
+impl TryFrom<&Json> for Value {
+    type Error = Error;
+
+    fn try_from(json: &Json) -> Result<Self, Self::Error> {
+        Ok(match json {
+            Json::Bool(value) => Value::Bool(*value),
+            Json::Number(number) => Value::Int(number_to_int(number)?),
+            Json::String(value) => Value::String(value.clone()),
+            Json::Array(values) => Value::List(
+                values
+                    .iter()
+                    .map(Value::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Json::Object(map) => Value::Map(
+                map.iter()
+                    .map(|(key, value)| Ok((key.clone(), Value::try_from(value)?)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Json::Null => return Err(INVALID),
+        })
+    }
+}
+
+fn number_to_int(number: &Number) -> Result<i64, Error> {
+    number
+        .as_i64()
+        .or_else(|| number.as_f64().map(|value| value as i64))
+        .ok_or(INVALID)
+}
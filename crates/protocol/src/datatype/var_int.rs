@@ -2,9 +2,10 @@ use bytes::{Buf, BufMut};
 
 use super::{CONTINUE_BIT, SEGMENT_BITS};
 
-use crate::Error;
+use crate::{Error, Limits};
 
-pub fn deserialize<B: Buf>(buf: &mut B) -> Result<i32, Error> {
+/// Reads a VarInt, whose length is bounded by its encoding rather than by `limits`.
+pub fn deserialize<B: Buf>(buf: &mut B, _limits: &Limits) -> Result<i32, Error> {
     let mut value = 0;
     let mut position = 0;
 
@@ -27,12 +28,12 @@ pub fn deserialize<B: Buf>(buf: &mut B) -> Result<i32, Error> {
     Err(Error::Overflow)
 }
 
-pub fn serialize<B: BufMut>(value: &i32, buf: &mut B) {
+pub fn serialize<B: BufMut>(value: &i32, buf: &mut B) -> Result<(), Error> {
     let mut value = *value;
     loop {
         if (value & !(SEGMENT_BITS as i32)) == 0 {
             buf.put_u8(value as u8);
-            break;
+            return Ok(());
         }
 
         buf.put_u8((value as u8 & SEGMENT_BITS) | CONTINUE_BIT);
@@ -40,6 +41,40 @@ pub fn serialize<B: BufMut>(value: &i32, buf: &mut B) {
     }
 }
 
+/// VarInt-prefixed array of VarInts, for use with `#[protocol(with = var_int::array)]`.
+pub mod array {
+    use bytes::{Buf, BufMut};
+
+    use crate::{Error, Limits};
+
+    pub fn deserialize<B: Buf>(buf: &mut B, limits: &Limits) -> Result<Vec<i32>, Error> {
+        let len = super::deserialize(buf, limits)?;
+        let len = usize::try_from(len).map_err(|_| Error::NegativeLength(len))?;
+        if len > limits.max_array_length {
+            return Err(Error::ArrayTooLong {
+                length: len,
+                max: limits.max_array_length,
+            });
+        }
+        let mut array = vec![];
+
+        for _ in 0..len {
+            array.push(super::deserialize(buf, limits)?);
+        }
+
+        Ok(array)
+    }
+
+    pub fn serialize<B: BufMut>(value: &[i32], buf: &mut B) -> Result<(), Error> {
+        super::serialize(&(value.len() as i32), buf)?;
+
+        for item in value {
+            super::serialize(item, buf)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use bytes::{Bytes, BytesMut};
@@ -65,7 +100,7 @@ mod test {
         for (expected_num, bytes) in TEST_CASES {
             let mut buf = Bytes::from_static(bytes);
 
-            let var_int = deserialize(&mut buf);
+            let var_int = deserialize(&mut buf, &Limits::DEFAULT);
             assert!(var_int.is_ok());
 
             let var_int = var_int.unwrap();
@@ -79,7 +114,7 @@ mod test {
     fn test_serialize() {
         for (num, reader) in TEST_CASES {
             let mut buf = BytesMut::new();
-            serialize(&num, &mut buf);
+            serialize(&num, &mut buf).unwrap();
             assert_eq!(&buf, reader);
         }
     }
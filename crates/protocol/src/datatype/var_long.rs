@@ -2,9 +2,10 @@ use bytes::{Buf, BufMut};
 
 use super::{CONTINUE_BIT, SEGMENT_BITS};
 
-use crate::Error;
+use crate::{Error, Limits};
 
-pub fn deserialize<B: Buf>(buf: &mut B) -> Result<i64, Error> {
+/// Reads a VarLong, whose length is bounded by its encoding rather than by `limits`.
+pub fn deserialize<B: Buf>(buf: &mut B, _limits: &Limits) -> Result<i64, Error> {
     let mut value = 0;
     let mut position = 0;
 
@@ -27,12 +28,12 @@ pub fn deserialize<B: Buf>(buf: &mut B) -> Result<i64, Error> {
     Err(Error::Overflow)
 }
 
-pub fn serialize<B: BufMut>(value: &i64, buf: &mut B) {
+pub fn serialize<B: BufMut>(value: &i64, buf: &mut B) -> Result<(), Error> {
     let mut value = *value;
     loop {
         if (value & !(SEGMENT_BITS as i64)) == 0 {
             buf.put_u8(value as u8);
-            break;
+            return Ok(());
         }
 
         buf.put_u8((value as u8 & SEGMENT_BITS) | CONTINUE_BIT);
@@ -77,7 +78,7 @@ mod test {
         for (expected_num, bytes) in TEST_CASES {
             let mut buf = Bytes::from_static(bytes);
 
-            let var_long = deserialize(&mut buf);
+            let var_long = deserialize(&mut buf, &Limits::DEFAULT);
             assert!(var_long.is_ok());
 
             let var_long = var_long.unwrap();
@@ -91,7 +92,7 @@ mod test {
     fn test_serialize() {
         for (num, reader) in TEST_CASES {
             let mut bytes = BytesMut::new();
-            serialize(&num, &mut bytes);
+            serialize(&num, &mut bytes).unwrap();
             assert_eq!(&bytes, reader);
         }
     }
@@ -10,10 +10,121 @@ pub enum Error {
     UnexpectedEof,
     #[error("input buffer contains to much data")]
     Overflow,
+    #[error("unknown discriminant {value} for {name}")]
+    UnknownDiscriminant { name: &'static str, value: i64 },
+    #[error("negative length {0}")]
+    NegativeLength(i32),
+    #[error("string of {length} bytes exceeds the maximum of {max}")]
+    StringTooLong { length: usize, max: usize },
+    #[error("array of {length} elements exceeds the maximum of {max}")]
+    ArrayTooLong { length: usize, max: usize },
+    #[error("expected {expected} elements but got {actual}")]
+    LengthMismatch { expected: usize, actual: usize },
+    #[error("packet {id:#04x} has {remaining} trailing bytes")]
+    TrailingBytes { id: i32, remaining: usize },
+    #[error("invalid nbt tag type {0}")]
+    InvalidNbtTag(u8),
+    #[error("nbt exceeds the maximum depth of {0}")]
+    NbtTooDeep(usize),
+    #[error("nbt exceeds the maximum size of {0} bytes")]
+    NbtTooLarge(usize),
+    #[error("invalid snbt at {position}: {message}")]
+    Snbt {
+        position: usize,
+        message: &'static str,
+    },
+    #[error("invalid text component: {0}")]
+    InvalidTextComponent(&'static str),
+    #[error("invalid server status: {0}")]
+    InvalidStatus(&'static str),
+    #[error("invalid favicon: {0}")]
+    InvalidFavicon(&'static str),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
     #[error("{0}")]
     Custom(&'static str),
     #[error(transparent)]
     Utf8(#[from] FromUtf8Error),
     #[error(transparent)]
     TryGetError(#[from] TryGetError),
+    #[error("{item}{path} at offset {offset}: {source}")]
+    Field {
+        /// Item the error occurred in, like `Hello` or `Action::Open`. Empty if the outermost
+        /// value is a sequence.
+        item: String,
+        /// Fields and elements leading from `item` to the error, like `.player_uuid` or
+        /// `.links[2].label`.
+        path: String,
+        /// Position of the innermost field or element relative to the start of `item`.
+        offset: usize,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Attributes the error to `field` of `item`, which started `offset` bytes into `item`.
+    ///
+    /// Errors of nested items are folded into a single path, like `Outer.inner.field`.
+    #[doc(hidden)]
+    pub fn in_field(self, item: &str, field: &str, offset: usize) -> Self {
+        self.nested(item.to_string(), format!(".{field}"), offset)
+    }
+
+    /// Attributes the error to element `index` of a sequence, which started `offset` bytes into
+    /// the sequence.
+    pub(crate) fn in_element(self, index: usize, offset: usize) -> Self {
+        self.nested(String::new(), format!("[{index}]"), offset)
+    }
+
+    /// Moves the position of a nested error `offset` bytes further, for values that start after
+    /// a prefix.
+    #[doc(hidden)]
+    pub fn shifted(self, offset: usize) -> Self {
+        match self {
+            Error::Field {
+                item,
+                path,
+                offset: inner_offset,
+                source,
+            } => Error::Field {
+                item,
+                path,
+                offset: offset + inner_offset,
+                source,
+            },
+            err => err,
+        }
+    }
+
+    /// Prepends `segment` to the path. The item of a nested error is replaced, as its start is
+    /// where `segment` starts.
+    fn nested(self, item: String, segment: String, offset: usize) -> Self {
+        match self {
+            Error::Field {
+                path,
+                offset: inner_offset,
+                source,
+                ..
+            } => Error::Field {
+                item,
+                path: segment + &path,
+                offset: offset + inner_offset,
+                source,
+            },
+            err => Error::Field {
+                item,
+                path: segment,
+                offset,
+                source: Box::new(err),
+            },
+        }
+    }
+
+    /// The underlying error, without the field context added by derived implementations.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Field { source, .. } => source.root(),
+            err => err,
+        }
+    }
 }
@@ -1,9 +1,18 @@
+// Lets the derive macros refer to `::minecrust_protocol` from within this crate as well.
+extern crate self as minecrust_protocol;
+
 pub mod datatype;
 mod deserialize;
 mod error;
+mod limits;
 pub mod packet;
+pub mod sequence;
 mod serialize;
+pub mod version;
 
+pub use bytes;
 pub use deserialize::*;
 pub use error::*;
+pub use limits::*;
+pub use minecrust_protocol_macro::{Deserialize, Serialize};
 pub use serialize::*;
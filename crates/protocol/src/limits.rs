@@ -0,0 +1,38 @@
+use crate::datatype::NbtLimits;
+
+/// Bounds and checks applied while decoding untrusted input.
+///
+/// The defaults match the limits enforced by vanilla. Frame decoders carry their own copy, and
+/// [`Deserialize`](crate::Deserialize) impls are handed the limits to read within.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum length of a packet frame on the wire.
+    pub max_frame_length: usize,
+    /// Maximum length of a packet after decompression.
+    pub max_uncompressed_length: usize,
+    /// Maximum length of a string in UTF-8 bytes.
+    pub max_string_length: usize,
+    /// Maximum number of elements in a length-prefixed array.
+    pub max_array_length: usize,
+    pub nbt: NbtLimits,
+    /// Rejects packets with bytes left over after decoding, to catch packet definitions that are
+    /// out of sync with the protocol. Off by default, as vanilla ignores them.
+    pub reject_trailing_bytes: bool,
+}
+
+impl Limits {
+    pub const DEFAULT: Self = Self {
+        max_frame_length: (1 << 21) - 1,
+        max_uncompressed_length: 1 << 23,
+        max_string_length: 32767 * 3,
+        max_array_length: 1 << 21,
+        nbt: NbtLimits::DEFAULT,
+        reject_trailing_bytes: false,
+    };
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
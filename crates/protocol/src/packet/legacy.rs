@@ -0,0 +1,215 @@
+//! Server list ping of clients before the netty rewrite (1.6 and older). These packets are not
+//! framed, strings are UTF-16BE prefixed with their length in code units.
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::Error;
+
+/// First byte of every legacy ping. Never starts a modern frame of a handshake.
+pub const PING_ID: u8 = 0xFE;
+const PAYLOAD_ID: u8 = 0x01;
+const PLUGIN_MESSAGE_ID: u8 = 0xFA;
+const KICK_ID: u8 = 0xFF;
+const PING_CHANNEL: &str = "MC|PingHost";
+
+/// Protocol reported to legacy clients, which no legacy client speaks.
+pub const INCOMPATIBLE_PROTOCOL: i32 = 127;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LegacyPing {
+    /// Beta 1.8 to 1.3, a lone `0xFE`.
+    Beta,
+    /// 1.4 and 1.5, `0xFE 0x01`.
+    V1_4,
+    /// 1.6, `0xFE 0x01` followed by a `MC|PingHost` plugin message.
+    V1_6 {
+        protocol_version: u8,
+        hostname: String,
+        port: i32,
+    },
+}
+
+impl LegacyPing {
+    /// Decodes a ping from everything received so far. Returns `None` while more data could
+    /// change the outcome; pass `complete` once no more data will arrive.
+    pub fn decode(buf: &[u8], complete: bool) -> Result<Option<Self>, Error> {
+        match buf {
+            [] => Err(Error::UnexpectedEof),
+            [PING_ID] | [PING_ID, PAYLOAD_ID] if !complete => Ok(None),
+            [PING_ID] => Ok(Some(LegacyPing::Beta)),
+            [PING_ID, PAYLOAD_ID, PLUGIN_MESSAGE_ID, rest @ ..] => {
+                match Self::decode_ping_host(&mut &rest[..]) {
+                    Err(Error::UnexpectedEof | Error::TryGetError(_)) if !complete => Ok(None),
+                    result => result.map(Some),
+                }
+            }
+            [PING_ID, PAYLOAD_ID, ..] => Ok(Some(LegacyPing::V1_4)),
+            [PING_ID, ..] => Ok(Some(LegacyPing::Beta)),
+            _ => Err(Error::Custom("not a legacy ping")),
+        }
+    }
+
+    fn decode_ping_host(buf: &mut &[u8]) -> Result<Self, Error> {
+        if read_string(buf)? != PING_CHANNEL {
+            return Err(Error::Custom("unexpected legacy ping channel"));
+        }
+        let length = buf.try_get_u16()? as usize;
+        if buf.remaining() < length {
+            return Err(Error::UnexpectedEof);
+        }
+        let mut data = &buf[..length];
+        let protocol_version = data.try_get_u8()?;
+        let hostname = read_string(&mut data)?;
+        let port = data.try_get_i32()?;
+
+        Ok(LegacyPing::V1_6 {
+            protocol_version,
+            hostname,
+            port,
+        })
+    }
+}
+
+/// Status sent back as the reason of a kick packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyPingResponse {
+    pub protocol_version: i32,
+    pub version_name: String,
+    /// Description with `§` codes, which are stripped for beta clients.
+    pub motd: String,
+    pub online_players: i32,
+    pub max_players: i32,
+}
+
+impl LegacyPingResponse {
+    pub fn encode(&self, ping: &LegacyPing) -> BytesMut {
+        let reason = match ping {
+            LegacyPing::Beta => format!(
+                "{}§{}§{}",
+                strip_codes(&self.motd),
+                self.online_players,
+                self.max_players
+            ),
+            LegacyPing::V1_4 | LegacyPing::V1_6 { .. } => format!(
+                "§1\0{}\0{}\0{}\0{}\0{}",
+                self.protocol_version,
+                self.version_name,
+                self.motd,
+                self.online_players,
+                self.max_players
+            ),
+        };
+
+        let mut buf = BytesMut::new();
+        buf.put_u8(KICK_ID);
+        write_string(&reason, &mut buf);
+        buf
+    }
+}
+
+/// Beta clients split the response at `§`, so formatting codes have to go.
+fn strip_codes(value: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '§' => {
+                chars.next();
+            }
+            char => stripped.push(char),
+        }
+    }
+    stripped
+}
+
+fn read_string(buf: &mut &[u8]) -> Result<String, Error> {
+    let length = buf.try_get_u16()? as usize;
+    if buf.remaining() < length * 2 {
+        return Err(Error::UnexpectedEof);
+    }
+    let units: Vec<u16> = (0..length).map(|_| buf.get_u16()).collect();
+    String::from_utf16(&units).map_err(|_| Error::Custom("invalid utf-16 string"))
+}
+
+fn write_string<B: BufMut>(value: &str, buf: &mut B) {
+    let units: Vec<u16> = value.encode_utf16().collect();
+    buf.put_u16(units.len() as u16);
+    for unit in units {
+        buf.put_u16(unit);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ping_host() -> Vec<u8> {
+        let mut data = vec![78];
+        write_string("localhost", &mut data);
+        data.put_i32(25565);
+
+        let mut buf = vec![PING_ID, PAYLOAD_ID, PLUGIN_MESSAGE_ID];
+        write_string(PING_CHANNEL, &mut buf);
+        buf.put_u16(data.len() as u16);
+        buf.extend_from_slice(&data);
+        buf
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(LegacyPing::decode(&[PING_ID], false).unwrap(), None);
+        assert_eq!(
+            LegacyPing::decode(&[PING_ID], true).unwrap(),
+            Some(LegacyPing::Beta)
+        );
+        assert_eq!(
+            LegacyPing::decode(&[PING_ID, PAYLOAD_ID], true).unwrap(),
+            Some(LegacyPing::V1_4)
+        );
+
+        let buf = ping_host();
+        assert_eq!(LegacyPing::decode(&buf[..10], false).unwrap(), None);
+        assert!(LegacyPing::decode(&buf[..10], true).is_err());
+        assert_eq!(
+            LegacyPing::decode(&buf, false).unwrap(),
+            Some(LegacyPing::V1_6 {
+                protocol_version: 78,
+                hostname: "localhost".to_string(),
+                port: 25565,
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_byte_by_byte() {
+        let buf = ping_host();
+        for end in 1..buf.len() {
+            assert_eq!(LegacyPing::decode(&buf[..end], false).unwrap(), None);
+        }
+        assert!(matches!(
+            LegacyPing::decode(&buf, false).unwrap(),
+            Some(LegacyPing::V1_6 { .. })
+        ));
+    }
+
+    #[test]
+    fn test_encode() {
+        let response = LegacyPingResponse {
+            protocol_version: INCOMPATIBLE_PROTOCOL,
+            version_name: "1.21.10".to_string(),
+            motd: "§aHi".to_string(),
+            online_players: 1,
+            max_players: 20,
+        };
+
+        let beta = response.encode(&LegacyPing::Beta);
+        assert_eq!(beta[..3], [KICK_ID, 0, 7]);
+        assert_eq!(read_string(&mut &beta[1..]).unwrap(), "Hi§1§20");
+
+        let modern = response.encode(&LegacyPing::V1_4);
+        assert_eq!(
+            read_string(&mut &modern[1..]).unwrap(),
+            ["§1", "127", "1.21.10", "§aHi", "1", "20"].join("\0")
+        );
+    }
+}
@@ -1,2 +1,85 @@
+/// Declares the enum of all packets a peer may send in one state, together with a `decode`
+/// that picks the packet struct by its ID and reads it within the given limits. IDs without a
+/// matching struct decode to `Unknown`.
+///
+/// Every listed packet is checked at compile time to belong to the given state and direction.
+macro_rules! packet_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident($state:ident, $direction:ident) {
+            $($variant:ident($packet:ty)),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        $vis enum $name {
+            $($variant($packet),)*
+            Unknown($crate::packet::RawPacket),
+        }
+
+        $(
+            const _: () = assert!(
+                matches!(<$packet as $crate::packet::Packet>::STATE, $crate::packet::State::$state)
+                    && matches!(
+                        <$packet as $crate::packet::Packet>::DIRECTION,
+                        $crate::packet::Direction::$direction
+                    ),
+                concat!(stringify!($packet), " does not belong into ", stringify!($name)),
+            );
+        )*
+
+        impl $name {
+            pub fn decode(
+                raw_packet: $crate::packet::RawPacket,
+                limits: &$crate::Limits,
+            ) -> Result<Self, $crate::Error> {
+                match raw_packet.id {
+                    $(
+                        id if id == <$packet as $crate::packet::Packet>::ID => {
+                            Ok(Self::$variant(raw_packet.try_into(limits)?))
+                        }
+                    )*
+                    _ => Ok(Self::Unknown(raw_packet)),
+                }
+            }
+        }
+    };
+}
+
+pub mod legacy;
+mod raw;
 pub mod unversioned;
 pub mod v773;
+
+pub use minecrust_protocol_macro::Packet;
+pub use raw::*;
+
+/// Protocol state a packet is exchanged in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Handshake,
+    Status,
+    Login,
+    Configuration,
+    Play,
+}
+
+/// Side of the connection a packet is sent to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Clientbound,
+    Serverbound,
+}
+
+/// Wire metadata of a packet, usually declared through `#[derive(Packet)]`:
+///
+/// ```ignore
+/// #[derive(Packet)]
+/// #[packet(id = 0x01, state = Status, direction = Serverbound)]
+/// pub struct PingRequest(pub i64);
+/// ```
+pub trait Packet {
+    const ID: i32;
+    const STATE: State;
+    const DIRECTION: Direction;
+}
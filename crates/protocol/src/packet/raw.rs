@@ -0,0 +1,47 @@
+use bytes::{Bytes, BytesMut};
+
+use crate::{Deserialize, Error, Limits, Serialize, datatype::var_int, packet::Packet};
+
+#[derive(Debug, Clone)]
+pub struct RawPacket {
+    pub id: i32,
+    pub data: Bytes,
+}
+
+impl TryFrom<BytesMut> for RawPacket {
+    type Error = Error;
+
+    fn try_from(mut data: BytesMut) -> Result<Self, Self::Error> {
+        let id = var_int::deserialize(&mut data, &Limits::DEFAULT)?;
+
+        Ok(RawPacket {
+            id,
+            data: data.freeze(),
+        })
+    }
+}
+
+impl RawPacket {
+    /// Serializes `packet`, failing if one of its fields cannot be sent as it is.
+    pub fn encode<P: Packet + Serialize>(packet: &P) -> Result<Self, Error> {
+        let mut buffer = BytesMut::new();
+        packet.serialize(&mut buffer)?;
+        Ok(Self {
+            id: P::ID,
+            data: buffer.freeze(),
+        })
+    }
+
+    /// Decodes the packet data within `limits`, rejecting trailing bytes if
+    /// [`Limits::reject_trailing_bytes`] is set.
+    pub fn try_into<P: Deserialize>(mut self, limits: &Limits) -> Result<P, Error> {
+        let packet = P::deserialize(&mut self.data, limits)?;
+        if limits.reject_trailing_bytes && !self.data.is_empty() {
+            return Err(Error::TrailingBytes {
+                id: self.id,
+                remaining: self.data.len(),
+            });
+        }
+        Ok(packet)
+    }
+}
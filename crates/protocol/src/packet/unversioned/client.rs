@@ -0,0 +1,39 @@
+use bytes::{Buf, BufMut};
+use minecrust_protocol_macro::Packet;
+
+use crate::{Deserialize, Error, Limits, Serialize, datatype::ServerStatus};
+
+packet_enum! {
+    #[allow(clippy::large_enum_variant)]
+    pub enum ClientboundStatus(Status, Clientbound) {
+        StatusResponse(StatusResponse),
+        PongResponse(PongResponse),
+    }
+}
+
+#[derive(Debug, Packet)]
+#[packet(id = 0x00, state = Status, direction = Clientbound)]
+pub struct StatusResponse {
+    pub status: ServerStatus,
+    /// Protocol of the client, which decides how the description is encoded. A decoded response
+    /// takes it from the version the server advertises.
+    pub protocol: i32,
+}
+
+impl Serialize for StatusResponse {
+    fn serialize<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        self.status.to_json_for(self.protocol).serialize(buf)
+    }
+}
+
+impl Deserialize for StatusResponse {
+    fn deserialize<B: Buf>(buf: &mut B, limits: &Limits) -> Result<Self, Error> {
+        let status = ServerStatus::deserialize(buf, limits)?;
+        let protocol = status.version.protocol;
+        Ok(Self { status, protocol })
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x01, state = Status, direction = Clientbound)]
+pub struct PongResponse(pub i64);
@@ -1,13 +1,35 @@
-use minecrust_protocol_macro::{Deserialize, Serialize};
+use minecrust_protocol_macro::{Deserialize, Packet, Serialize};
 
 use crate::datatype::{Intent, var_int};
 
-/// Handshake | 0x00
-#[derive(Debug, Deserialize, Serialize)]
+packet_enum! {
+    pub enum ServerboundHandshake(Handshake, Serverbound) {
+        Intention(Intention),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x00, state = Handshake, direction = Serverbound)]
 pub struct Intention {
     #[protocol(with = var_int)]
     pub protocol_version: i32,
+    #[protocol(max_len = 255)]
     pub server_address: String,
     pub server_port: u16,
     pub intent: Intent,
 }
+
+packet_enum! {
+    pub enum ServerboundStatus(Status, Serverbound) {
+        StatusRequest(StatusRequest),
+        PingRequest(PingRequest),
+    }
+}
+
+#[derive(Debug, Deserialize, Packet)]
+#[packet(id = 0x00, state = Status, direction = Serverbound)]
+pub struct StatusRequest;
+
+#[derive(Debug, Deserialize, Packet)]
+#[packet(id = 0x01, state = Status, direction = Serverbound)]
+pub struct PingRequest(pub i64);
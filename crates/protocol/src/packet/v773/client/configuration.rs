@@ -0,0 +1,190 @@
+use bytes::Bytes;
+use minecrust_protocol_macro::{Deserialize, Packet, Serialize};
+use uuid::Uuid;
+
+use crate::datatype::{KnownPack, Nbt, TextComponent, var_int};
+
+packet_enum! {
+    pub enum ClientboundConfiguration(Configuration, Clientbound) {
+        CookieRequest(CookieRequest),
+        CustomPayload(CustomPayload),
+        Disconnect(Disconnect),
+        FinishConfiguration(FinishConfiguration),
+        KeepAlive(KeepAlive),
+        Ping(Ping),
+        ResetChat(ResetChat),
+        RegistryData(RegistryData),
+        ResourcePackPop(ResourcePackPop),
+        ResourcePackPush(ResourcePackPush),
+        StoreCookie(StoreCookie),
+        Transfer(Transfer),
+        UpdateEnabledFeatures(UpdateEnabledFeatures),
+        UpdateTags(UpdateTags),
+        SelectKnownPacks(SelectKnownPacks),
+        CustomReportDetails(CustomReportDetails),
+        ServerLinks(ServerLinks),
+        ClearDialog(ClearDialog),
+        ShowDialog(ShowDialog),
+        CodeOfConduct(CodeOfConduct),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x00, state = Configuration, direction = Clientbound)]
+pub struct CookieRequest(pub String);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x01, state = Configuration, direction = Clientbound)]
+pub struct CustomPayload {
+    pub channel: String,
+    #[protocol(rest)]
+    pub data: Bytes,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x02, state = Configuration, direction = Clientbound)]
+pub struct Disconnect(pub TextComponent);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x03, state = Configuration, direction = Clientbound)]
+pub struct FinishConfiguration;
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x04, state = Configuration, direction = Clientbound)]
+pub struct KeepAlive(pub i64);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x05, state = Configuration, direction = Clientbound)]
+pub struct Ping(pub i32);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x06, state = Configuration, direction = Clientbound)]
+pub struct ResetChat;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RegistryEntry {
+    pub entry_id: String,
+    /// Omitted when the client already knows the entry from a known pack.
+    pub data: Option<Nbt>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x07, state = Configuration, direction = Clientbound)]
+pub struct RegistryData {
+    pub registry_id: String,
+    pub entries: Vec<RegistryEntry>,
+}
+
+/// Removes the resource pack with the given UUID, or all of them if `None`.
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x08, state = Configuration, direction = Clientbound)]
+pub struct ResourcePackPop(pub Option<Uuid>);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x09, state = Configuration, direction = Clientbound)]
+pub struct ResourcePackPush {
+    pub uuid: Uuid,
+    pub url: String,
+    pub hash: String,
+    pub forced: bool,
+    pub prompt_message: Option<TextComponent>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x0A, state = Configuration, direction = Clientbound)]
+pub struct StoreCookie {
+    pub key: String,
+    #[protocol(max_len = 5120)]
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x0B, state = Configuration, direction = Clientbound)]
+pub struct Transfer {
+    pub host: String,
+    #[protocol(with = var_int)]
+    pub port: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x0C, state = Configuration, direction = Clientbound)]
+pub struct UpdateEnabledFeatures(pub Vec<String>);
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Tag {
+    pub name: String,
+    #[protocol(with = var_int::array)]
+    pub entries: Vec<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RegistryTags {
+    pub registry: String,
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x0D, state = Configuration, direction = Clientbound)]
+pub struct UpdateTags(pub Vec<RegistryTags>);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x0E, state = Configuration, direction = Clientbound)]
+pub struct SelectKnownPacks(pub Vec<KnownPack>);
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReportDetail {
+    pub title: String,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x0F, state = Configuration, direction = Clientbound)]
+pub struct CustomReportDetails(pub Vec<ReportDetail>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[protocol(repr = var_int)]
+pub enum ServerLinkType {
+    BugReport,
+    CommunityGuidelines,
+    Support,
+    Status,
+    Feedback,
+    Community,
+    Website,
+    Forums,
+    News,
+    Announcements,
+}
+
+/// Encoded as a boolean telling whether the label is built in, followed by the label itself.
+#[derive(Debug, Deserialize, Serialize)]
+#[protocol(repr = u8)]
+pub enum ServerLinkLabel {
+    #[protocol(discriminant = 1)]
+    BuiltIn(ServerLinkType),
+    #[protocol(discriminant = 0)]
+    Custom(Box<TextComponent>),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ServerLink {
+    pub label: ServerLinkLabel,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x10, state = Configuration, direction = Clientbound)]
+pub struct ServerLinks(pub Vec<ServerLink>);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x11, state = Configuration, direction = Clientbound)]
+pub struct ClearDialog;
+
+/// Encoded dialog holder: the VarInt registry ID plus one, or `0` followed by the inline dialog NBT.
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x12, state = Configuration, direction = Clientbound)]
+pub struct ShowDialog(#[protocol(rest)] pub Bytes);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x13, state = Configuration, direction = Clientbound)]
+pub struct CodeOfConduct(pub String);
@@ -1,32 +1,51 @@
 use bytes::Bytes;
-use minecrust_protocol_macro::Serialize;
+use minecrust_protocol_macro::{Deserialize, Packet, Serialize};
 
-use crate::datatype::{GameProfile, TextComponent, var_int};
+use crate::datatype::{GameProfile, TextComponent, text_component, var_int};
 
-#[derive(Debug, Serialize)]
-pub struct LoginDisconnect(pub TextComponent);
+packet_enum! {
+    pub enum ClientboundLogin(Login, Clientbound) {
+        LoginDisconnect(LoginDisconnect),
+        Hello(Hello),
+        LoginFinished(LoginFinished),
+        LoginCompression(LoginCompression),
+        CustomQuery(CustomQuery),
+        CookieRequest(CookieRequest),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x00, state = Login, direction = Clientbound)]
+pub struct LoginDisconnect(#[protocol(with = text_component::json)] pub TextComponent);
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x01, state = Login, direction = Clientbound)]
 pub struct Hello {
     pub server_id: String,
     pub public_key: Vec<u8>,
+    #[protocol(prefix = var_int)]
     pub verify_token: [u8; 32],
     pub should_authenticate: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x02, state = Login, direction = Clientbound)]
 pub struct LoginFinished(pub GameProfile);
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x03, state = Login, direction = Clientbound)]
 pub struct LoginCompression(#[protocol(with = var_int)] pub i32);
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x04, state = Login, direction = Clientbound)]
 pub struct CustomQuery {
     #[protocol(with = var_int)]
     pub message_id: i32,
     pub channel: String,
+    #[protocol(rest)]
     pub data: Bytes,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x05, state = Login, direction = Clientbound)]
 pub struct CookieRequest(pub String);
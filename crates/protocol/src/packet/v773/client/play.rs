@@ -0,0 +1,14 @@
+use minecrust_protocol_macro::{Deserialize, Packet, Serialize};
+
+use crate::datatype::TextComponent;
+
+packet_enum! {
+    #[allow(clippy::large_enum_variant)]
+    pub enum ClientboundPlay(Play, Clientbound) {
+        Disconnect(Disconnect),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x20, state = Play, direction = Clientbound)]
+pub struct Disconnect(pub TextComponent);
@@ -1,7 +1,2 @@
-use minecrust_protocol_macro::Serialize;
-
-#[derive(Debug, Serialize)]
-pub struct StatusResponse(pub String); // TODO: Json Status Response
-
-#[derive(Debug, Serialize)]
-pub struct PongResponse(pub i64);
+//! The status packets have not changed since the netty rewrite.
+pub use crate::packet::unversioned::client::{ClientboundStatus, PongResponse, StatusResponse};
@@ -0,0 +1,121 @@
+use bytes::Bytes;
+use minecrust_protocol_macro::{Deserialize, Packet, Serialize};
+use uuid::Uuid;
+
+use crate::datatype::{KnownPack, Nbt, nbt};
+
+packet_enum! {
+    pub enum ServerboundConfiguration(Configuration, Serverbound) {
+        ClientInformation(ClientInformation),
+        CookieResponse(CookieResponse),
+        CustomPayload(CustomPayload),
+        FinishConfiguration(FinishConfiguration),
+        KeepAlive(KeepAlive),
+        Pong(Pong),
+        ResourcePack(ResourcePack),
+        SelectKnownPacks(SelectKnownPacks),
+        CustomClickAction(CustomClickAction),
+        AcceptCodeOfConduct(AcceptCodeOfConduct),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[protocol(repr = var_int)]
+pub enum ChatMode {
+    Enabled,
+    CommandsOnly,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[protocol(repr = var_int)]
+pub enum MainHand {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[protocol(repr = var_int)]
+pub enum ParticleStatus {
+    All,
+    Decreased,
+    Minimal,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x00, state = Configuration, direction = Serverbound)]
+pub struct ClientInformation {
+    pub locale: String,
+    pub view_distance: i8,
+    pub chat_mode: ChatMode,
+    pub chat_colors: bool,
+    pub displayed_skin_parts: u8,
+    pub main_hand: MainHand,
+    pub enable_text_filtering: bool,
+    pub allow_server_listings: bool,
+    pub particle_status: ParticleStatus,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x01, state = Configuration, direction = Serverbound)]
+pub struct CookieResponse {
+    pub key: String,
+    #[protocol(max_len = 5120)]
+    pub data: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x02, state = Configuration, direction = Serverbound)]
+pub struct CustomPayload {
+    pub channel: String,
+    #[protocol(rest)]
+    pub data: Bytes,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x03, state = Configuration, direction = Serverbound)]
+pub struct FinishConfiguration;
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x04, state = Configuration, direction = Serverbound)]
+pub struct KeepAlive(pub i64);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x05, state = Configuration, direction = Serverbound)]
+pub struct Pong(pub i32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[protocol(repr = var_int)]
+pub enum ResourcePackResult {
+    SuccessfullyDownloaded,
+    Declined,
+    FailedToDownload,
+    Accepted,
+    Downloaded,
+    InvalidUrl,
+    FailedToReload,
+    Discarded,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x06, state = Configuration, direction = Serverbound)]
+pub struct ResourcePack {
+    pub uuid: Uuid,
+    pub result: ResourcePackResult,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x07, state = Configuration, direction = Serverbound)]
+pub struct SelectKnownPacks(pub Vec<KnownPack>);
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x08, state = Configuration, direction = Serverbound)]
+pub struct CustomClickAction {
+    pub id: String,
+    #[protocol(with = nbt::optional)]
+    pub payload: Option<Nbt>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x09, state = Configuration, direction = Serverbound)]
+pub struct AcceptCodeOfConduct;
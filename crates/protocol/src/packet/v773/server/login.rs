@@ -1,47 +1,51 @@
 use bytes::Bytes;
-use minecrust_protocol_macro::{Deserialize, Serialize};
+use minecrust_protocol_macro::{Deserialize, Packet, Serialize};
 use uuid::Uuid;
 
 use crate::datatype::var_int;
 
-#[derive(Debug)]
-pub enum LoginPacket {
-    Hello(Hello),
-    Key(Key),
-    CustomQueryAnswer(CustomQueryAnswer),
-    LoginAcknowledged(LoginAcknowledged),
-    CookieRequest(CookieResponse),
+packet_enum! {
+    pub enum ServerboundLogin(Login, Serverbound) {
+        Hello(Hello),
+        Key(Key),
+        CustomQueryAnswer(CustomQueryAnswer),
+        LoginAcknowledged(LoginAcknowledged),
+        CookieResponse(CookieResponse),
+    }
 }
 
-/// Login | 0x00
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x00, state = Login, direction = Serverbound)]
 pub struct Hello {
+    #[protocol(max_len = 16)]
     pub name: String,
     pub player_uuid: Uuid,
 }
 
-/// Login | 0x01
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x01, state = Login, direction = Serverbound)]
 pub struct Key {
     pub shared_secret: Vec<u8>,
     pub verify_token: Vec<u8>,
 }
 
-/// Login | 0x02
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x02, state = Login, direction = Serverbound)]
 pub struct CustomQueryAnswer {
     #[protocol(with = var_int)]
     pub message_id: i32,
+    #[protocol(rest)]
     pub data: Option<Bytes>,
 }
 
-/// Login | 0x03
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x03, state = Login, direction = Serverbound)]
 pub struct LoginAcknowledged;
 
-/// Login | 0x04
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Packet)]
+#[packet(id = 0x04, state = Login, direction = Serverbound)]
 pub struct CookieResponse {
     pub key: String,
+    #[protocol(max_len = 5120)]
     pub data: Option<Vec<u8>>,
 }
@@ -1,5 +1,2 @@
-use minecrust_protocol_macro::Deserialize;
-
-/// Status | 0x01
-#[derive(Debug, Deserialize)]
-pub struct PingRequest(pub i64);
+//! The status packets have not changed since the netty rewrite.
+pub use crate::packet::unversioned::server::{PingRequest, ServerboundStatus, StatusRequest};
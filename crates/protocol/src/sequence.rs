@@ -0,0 +1,291 @@
+//! Length encodings of sequences, chosen per field through the derive attributes
+//! `#[protocol(prefix = var_int|none)]`, `#[protocol(rest)]` and `#[protocol(max_len = N)]`.
+//!
+//! Unless a field chooses otherwise, `Bytes` extends to the end of the packet and every other
+//! sequence, fixed-size arrays included, is prefixed with its length. Without a prefix,
+//! fixed-size arrays read their size and every other sequence extends to the end of the packet.
+//!
+//! On fields spelled `Option<_>`, the attributes apply to the contained value. The derive only
+//! sees the spelling, so aliases of `Option` are not recognized.
+
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::{Deserialize, Error, Limits, Serialize, datatype::var_int};
+
+/// Sequence whose length can be written in different ways.
+pub trait Sequence {
+    /// Length of sequences whose size is part of their type.
+    const FIXED_LEN: Option<usize> = None;
+
+    /// Whether the length is prefixed unless a field chooses otherwise.
+    const PREFIXED: bool = true;
+
+    /// Length written in a prefix.
+    fn wire_len(&self) -> usize;
+
+    /// Length compared against `max_len`.
+    fn bounded_len(&self) -> usize {
+        self.wire_len()
+    }
+
+    /// Largest prefix a sequence bounded by `max_len` can have.
+    fn max_wire_len(max_len: usize) -> usize {
+        max_len
+    }
+
+    /// Largest prefix accepted by `limits`.
+    fn limit(limits: &Limits) -> usize {
+        limits.max_array_length
+    }
+
+    fn too_long(length: usize, max: usize) -> Error {
+        Error::ArrayTooLong { length, max }
+    }
+}
+
+pub trait SerializeSequence: Sequence {
+    fn serialize_elements<B: BufMut>(&self, buf: &mut B) -> Result<(), Error>;
+}
+
+pub trait DeserializeSequence: Sequence + Sized {
+    /// Reads `len` elements, or everything left in `buf` if `None`.
+    fn deserialize_elements<B: Buf>(
+        buf: &mut B,
+        limits: &Limits,
+        len: Option<usize>,
+    ) -> Result<Self, Error>;
+}
+
+/// Writes `value`, prefixed with its length unless `prefixed` is `false`. Without an explicit
+/// choice, [`Sequence::PREFIXED`] decides.
+///
+/// Fails without writing anything if `value` exceeds `max_len`, as the peer would reject it.
+pub fn serialize<S: SerializeSequence, B: BufMut>(
+    value: &S,
+    buf: &mut B,
+    prefixed: Option<bool>,
+    max_len: Option<usize>,
+) -> Result<(), Error> {
+    if let Some(max) = max_len
+        && value.bounded_len() > max
+    {
+        return Err(S::too_long(value.bounded_len(), max));
+    }
+    if prefixed.unwrap_or(S::PREFIXED) {
+        var_int::serialize(&(value.wire_len() as i32), buf)?;
+    }
+    value.serialize_elements(buf)
+}
+
+/// Reads a sequence written by [`serialize`] with the same options.
+pub fn deserialize<S: DeserializeSequence, B: Buf>(
+    buf: &mut B,
+    limits: &Limits,
+    prefixed: Option<bool>,
+    max_len: Option<usize>,
+) -> Result<S, Error> {
+    let start = buf.remaining();
+    let len = if prefixed.unwrap_or(S::PREFIXED) {
+        let len = var_int::deserialize(buf, limits)?;
+        let len = usize::try_from(len).map_err(|_| Error::NegativeLength(len))?;
+        let limit = S::limit(limits);
+        let max = max_len.map_or(limit, |max| S::max_wire_len(max).min(limit));
+        if len > max {
+            return Err(S::too_long(len, max));
+        }
+        if let Some(expected) = S::FIXED_LEN
+            && len != expected
+        {
+            return Err(Error::LengthMismatch {
+                expected,
+                actual: len,
+            });
+        }
+        Some(len)
+    } else {
+        S::FIXED_LEN
+    };
+
+    let prefix = start - buf.remaining();
+    let value = S::deserialize_elements(buf, limits, len).map_err(|err| err.shifted(prefix))?;
+    if let Some(max) = max_len
+        && value.bounded_len() > max
+    {
+        return Err(S::too_long(value.bounded_len(), max));
+    }
+    Ok(value)
+}
+
+/// Number of bytes to read, checked against what is left in `buf`.
+fn byte_len<B: Buf>(buf: &B, len: Option<usize>) -> Result<usize, Error> {
+    let len = len.unwrap_or(buf.remaining());
+    if len > buf.remaining() {
+        return Err(Error::UnexpectedEof);
+    }
+    Ok(len)
+}
+
+/// Reads element `index` of a sequence whose elements started when `start` bytes remained.
+fn deserialize_element<T: Deserialize, B: Buf>(
+    buf: &mut B,
+    limits: &Limits,
+    index: usize,
+    start: usize,
+) -> Result<T, Error> {
+    let offset = start - buf.remaining();
+    T::deserialize(buf, limits).map_err(|err| err.in_element(index, offset))
+}
+
+impl<T> Sequence for Vec<T> {
+    fn wire_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Serialize> SerializeSequence for Vec<T> {
+    fn serialize_elements<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        for item in self {
+            item.serialize(buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Deserialize> DeserializeSequence for Vec<T> {
+    fn deserialize_elements<B: Buf>(
+        buf: &mut B,
+        limits: &Limits,
+        len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let start = buf.remaining();
+        let Some(len) = len else {
+            let max = Self::limit(limits);
+            let mut items = vec![];
+            while buf.has_remaining() {
+                if items.len() == max {
+                    return Err(Self::too_long(items.len() + 1, max));
+                }
+                // Elements that read nothing would be repeated until memory runs out.
+                let remaining = buf.remaining();
+                items.push(deserialize_element(buf, limits, items.len(), start)?);
+                if buf.remaining() == remaining {
+                    return Err(Error::Custom("element of an unprefixed sequence is empty"));
+                }
+            }
+            return Ok(items);
+        };
+
+        // The length is untrusted, so only reserve what the input could possibly hold.
+        let mut items = Vec::with_capacity(len.min(buf.remaining()));
+        for index in 0..len {
+            items.push(deserialize_element(buf, limits, index, start)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T, const N: usize> Sequence for [T; N] {
+    const FIXED_LEN: Option<usize> = Some(N);
+
+    fn wire_len(&self) -> usize {
+        N
+    }
+}
+
+impl<T: Serialize, const N: usize> SerializeSequence for [T; N] {
+    fn serialize_elements<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        for item in self {
+            item.serialize(buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Deserialize, const N: usize> DeserializeSequence for [T; N] {
+    fn deserialize_elements<B: Buf>(
+        buf: &mut B,
+        limits: &Limits,
+        _len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let start = buf.remaining();
+        let mut items = Vec::with_capacity(N);
+        for index in 0..N {
+            items.push(deserialize_element(buf, limits, index, start)?);
+        }
+        match items.try_into() {
+            Ok(items) => Ok(items),
+            Err(_) => unreachable!("exactly {N} items were read"),
+        }
+    }
+}
+
+/// Raw data that usually fills the rest of a packet, such as plugin message payloads.
+impl Sequence for Bytes {
+    const PREFIXED: bool = false;
+
+    fn wire_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl SerializeSequence for Bytes {
+    fn serialize_elements<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        buf.put_slice(self);
+        Ok(())
+    }
+}
+
+impl DeserializeSequence for Bytes {
+    fn deserialize_elements<B: Buf>(
+        buf: &mut B,
+        _limits: &Limits,
+        len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let len = byte_len(buf, len)?;
+        Ok(buf.copy_to_bytes(len))
+    }
+}
+
+/// Strings are prefixed with their length in bytes, but bounded in UTF-16 code units like
+/// vanilla does.
+impl Sequence for String {
+    fn wire_len(&self) -> usize {
+        self.len()
+    }
+
+    fn bounded_len(&self) -> usize {
+        self.encode_utf16().count()
+    }
+
+    fn max_wire_len(max_len: usize) -> usize {
+        max_len * 3
+    }
+
+    fn limit(limits: &Limits) -> usize {
+        limits.max_string_length
+    }
+
+    fn too_long(length: usize, max: usize) -> Error {
+        Error::StringTooLong { length, max }
+    }
+}
+
+impl SerializeSequence for String {
+    fn serialize_elements<B: BufMut>(&self, buf: &mut B) -> Result<(), Error> {
+        buf.put_slice(self.as_bytes());
+        Ok(())
+    }
+}
+
+impl DeserializeSequence for String {
+    fn deserialize_elements<B: Buf>(
+        buf: &mut B,
+        _limits: &Limits,
+        len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let len = byte_len(buf, len)?;
+        let mut bytes = vec![0u8; len];
+        buf.try_copy_to_slice(&mut bytes)?;
+        Ok(String::from_utf8(bytes)?)
+    }
+}
@@ -0,0 +1,132 @@
+//! Protocol numbers of the released game versions since the netty rewrite.
+
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Release {
+    pub protocol: i32,
+    /// First and last game version speaking this protocol.
+    pub first: &'static str,
+    pub last: &'static str,
+}
+
+impl Release {
+    pub fn name(&self) -> String {
+        if self.first == self.last {
+            self.first.to_string()
+        } else {
+            format!("{}-{}", self.first, self.last)
+        }
+    }
+}
+
+macro_rules! releases {
+    ($($protocol:literal => $first:literal $(..= $last:literal)?),* $(,)?) => {
+        /// Ordered by protocol number.
+        pub const RELEASES: &[Release] = &[
+            $(Release {
+                protocol: $protocol,
+                first: $first,
+                last: releases!(@last $first $($last)?),
+            }),*
+        ];
+    };
+    (@last $first:literal) => { $first };
+    (@last $first:literal $last:literal) => { $last };
+}
+
+releases! {
+    4 => "1.7.2" ..= "1.7.5",
+    5 => "1.7.6" ..= "1.7.10",
+    47 => "1.8" ..= "1.8.9",
+    107 => "1.9",
+    108 => "1.9.1",
+    109 => "1.9.2",
+    110 => "1.9.3" ..= "1.9.4",
+    210 => "1.10" ..= "1.10.2",
+    315 => "1.11",
+    316 => "1.11.1" ..= "1.11.2",
+    335 => "1.12",
+    338 => "1.12.1",
+    340 => "1.12.2",
+    393 => "1.13",
+    401 => "1.13.1",
+    404 => "1.13.2",
+    477 => "1.14",
+    480 => "1.14.1",
+    485 => "1.14.2",
+    490 => "1.14.3",
+    498 => "1.14.4",
+    573 => "1.15",
+    575 => "1.15.1",
+    578 => "1.15.2",
+    735 => "1.16",
+    736 => "1.16.1",
+    751 => "1.16.2",
+    753 => "1.16.3",
+    754 => "1.16.4" ..= "1.16.5",
+    755 => "1.17",
+    756 => "1.17.1",
+    757 => "1.18" ..= "1.18.1",
+    758 => "1.18.2",
+    759 => "1.19",
+    760 => "1.19.1" ..= "1.19.2",
+    761 => "1.19.3",
+    762 => "1.19.4",
+    763 => "1.20" ..= "1.20.1",
+    764 => "1.20.2",
+    765 => "1.20.3" ..= "1.20.4",
+    766 => "1.20.5" ..= "1.20.6",
+    767 => "1.21" ..= "1.21.1",
+    768 => "1.21.2" ..= "1.21.3",
+    769 => "1.21.4",
+    770 => "1.21.5",
+    771 => "1.21.6",
+    772 => "1.21.7" ..= "1.21.8",
+    773 => "1.21.9" ..= "1.21.10",
+}
+
+pub fn release(protocol: i32) -> Option<&'static Release> {
+    RELEASES
+        .binary_search_by_key(&protocol, |release| release.protocol)
+        .ok()
+        .map(|index| &RELEASES[index])
+}
+
+/// Names the game versions covered by `range`, e.g. `1.21-1.21.10`.
+pub fn range_name(range: &RangeInclusive<i32>) -> Option<String> {
+    let mut releases = RELEASES
+        .iter()
+        .filter(|release| range.contains(&release.protocol));
+    let first = releases.next()?;
+    let last = releases.next_back().unwrap_or(first);
+    Some(if first == last {
+        first.name()
+    } else {
+        format!("{}-{}", first.first, last.last)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_releases_are_sorted() {
+        assert!(
+            RELEASES
+                .windows(2)
+                .all(|pair| pair[0].protocol < pair[1].protocol)
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(release(773).unwrap().name(), "1.21.9-1.21.10");
+        assert_eq!(release(769).unwrap().name(), "1.21.4");
+        assert_eq!(release(1), None);
+        assert_eq!(range_name(&(767..=773)).unwrap(), "1.21-1.21.10");
+        assert_eq!(range_name(&(770..=770)).unwrap(), "1.21.5");
+        assert_eq!(range_name(&(1..=3)), None);
+    }
+}
@@ -0,0 +1,310 @@
+use minecrust_protocol::{
+    Deserialize, Error, Limits, Serialize,
+    bytes::{Bytes, BytesMut},
+    datatype::{Intent, var_int},
+    packet::{Direction, Packet, RawPacket, State, v773::server::login::Hello},
+};
+
+mod reexport {
+    pub use minecrust_protocol as protocol;
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PluginPayload {
+    #[protocol(with = var_int)]
+    id: i32,
+    channel: String,
+    flags: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Wrapper<T>(T, i64);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[protocol(crate = "reexport::protocol")]
+struct Renamed {
+    value: i16,
+}
+
+fn round_trip<T: Serialize + Deserialize>(value: &T) -> T {
+    let mut buf = BytesMut::new();
+    value.serialize(&mut buf).unwrap();
+    let decoded = T::deserialize(&mut buf, &Limits::DEFAULT).expect("value should deserialize");
+    assert!(buf.is_empty(), "deserialize should consume all bytes");
+    decoded
+}
+
+#[test]
+fn test_named_struct() {
+    let payload = PluginPayload {
+        id: 300,
+        channel: "minecrust:test".to_string(),
+        flags: Some(7),
+    };
+    assert_eq!(round_trip(&payload), payload);
+}
+
+#[test]
+fn test_generic_tuple_struct() {
+    let wrapper = Wrapper(String::from("hello"), -42);
+    assert_eq!(round_trip(&wrapper), wrapper);
+}
+
+#[test]
+fn test_crate_override() {
+    let renamed = Renamed { value: -1234 };
+    assert_eq!(round_trip(&renamed), renamed);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[protocol(repr = u8)]
+enum Hand {
+    Left,
+    Right,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[protocol(repr = i32)]
+enum Sparse {
+    First = -1,
+    Second,
+    Third = 10,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Label {
+    #[protocol(discriminant = 4)]
+    BuiltIn(#[protocol(with = var_int)] i32),
+    Custom {
+        text: String,
+        hand: Hand,
+    },
+    Empty,
+}
+
+#[test]
+fn test_c_like_enum() {
+    assert_eq!(round_trip(&Hand::Right), Hand::Right);
+    assert_eq!(round_trip(&Sparse::Second), Sparse::Second);
+    assert_eq!(round_trip(&Sparse::Third), Sparse::Third);
+
+    let mut buf = BytesMut::new();
+    Sparse::Second.serialize(&mut buf).unwrap();
+    assert_eq!(&buf[..], &0i32.to_be_bytes());
+}
+
+#[test]
+fn test_tagged_enum() {
+    let custom = Label::Custom {
+        text: String::from("Discord"),
+        hand: Hand::Left,
+    };
+    assert_eq!(round_trip(&Label::BuiltIn(300)), Label::BuiltIn(300));
+    assert_eq!(round_trip(&custom), custom);
+    assert_eq!(round_trip(&Label::Empty), Label::Empty);
+
+    let mut buf = BytesMut::new();
+    Label::Empty.serialize(&mut buf).unwrap();
+    assert_eq!(&buf[..], &[0x06]);
+}
+
+#[test]
+fn test_unknown_discriminant() {
+    let mut buf = BytesMut::from(&[0x02][..]);
+    let result = Hand::deserialize(&mut buf, &Limits::DEFAULT);
+    assert!(matches!(
+        result,
+        Err(Error::UnknownDiscriminant {
+            name: "Hand",
+            value: 2
+        })
+    ));
+
+    let mut buf = BytesMut::from(&[0x07][..]);
+    assert!(Intent::deserialize(&mut buf, &Limits::DEFAULT).is_err());
+}
+
+#[test]
+fn test_error_context() {
+    let mut buf = BytesMut::new();
+    String::from("Notch").serialize(&mut buf).unwrap();
+    buf.extend_from_slice(&[0; 4]);
+    let err = Hello::deserialize(&mut buf, &Limits::DEFAULT).unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::Field { item, path, offset: 6, .. } if item == "Hello" && path == ".player_uuid"
+    ));
+    assert!(matches!(err.root(), Error::TryGetError(_)));
+
+    let mut buf = BytesMut::new();
+    var_int::serialize(&300, &mut buf).unwrap();
+    var_int::serialize(&10, &mut buf).unwrap();
+    let err = Wrapper::<PluginPayload>::deserialize(&mut buf, &Limits::DEFAULT).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Wrapper.0.channel at offset 2: ")
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Listing {
+    id: u8,
+    entries: Vec<PluginPayload>,
+    payload: Option<PluginPayload>,
+}
+
+#[test]
+fn test_nested_error_context() {
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(&[1, 2]);
+    PluginPayload {
+        id: 300,
+        channel: String::from("a"),
+        flags: None,
+    }
+    .serialize(&mut buf)
+    .unwrap();
+    buf.extend_from_slice(&[1, 10]);
+    let err = Listing::deserialize(&mut buf, &Limits::DEFAULT).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Listing.entries[1].channel at offset 8: ")
+    );
+
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(&[1, 0, 1]);
+    var_int::serialize(&300, &mut buf).unwrap();
+    buf.extend_from_slice(&[10]);
+    let err = Listing::deserialize(&mut buf, &Limits::DEFAULT).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Listing.payload.channel at offset 5: ")
+    );
+
+    let mut buf = BytesMut::new();
+    Label::Custom {
+        text: String::from("a"),
+        hand: Hand::Left,
+    }
+    .serialize(&mut buf)
+    .unwrap();
+    buf[3] = 9;
+    let err = Wrapper::<Label>::deserialize(&mut buf, &Limits::DEFAULT).unwrap_err();
+    assert!(err.to_string().starts_with("Wrapper.0.hand at offset 3: "));
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Packet)]
+#[packet(id = 0x2a, state = Configuration, direction = Serverbound)]
+struct CustomPayload {
+    data: String,
+}
+
+#[test]
+fn test_packet_metadata() {
+    assert_eq!(CustomPayload::ID, 0x2a);
+    assert_eq!(CustomPayload::STATE, State::Configuration);
+    assert_eq!(CustomPayload::DIRECTION, Direction::Serverbound);
+
+    let payload = CustomPayload {
+        data: String::from("brand"),
+    };
+    let raw_packet = RawPacket::encode(&payload).unwrap();
+    assert_eq!(raw_packet.id, 0x2a);
+    assert_eq!(
+        raw_packet
+            .try_into::<CustomPayload>(&Limits::DEFAULT)
+            .unwrap(),
+        payload
+    );
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Sequences {
+    fixed: [u8; 2],
+    #[protocol(prefix = var_int)]
+    prefixed: [u8; 2],
+    #[protocol(prefix = none)]
+    unprefixed: [i16; 1],
+    #[protocol(max_len = 4)]
+    name: String,
+    #[protocol(max_len = 2)]
+    cookie: Option<Vec<u8>>,
+    #[protocol(prefix = var_int)]
+    bytes: Bytes,
+    #[protocol(rest)]
+    rest: Bytes,
+}
+
+#[test]
+fn test_sequence_attributes() {
+    let sequences = Sequences {
+        fixed: [1, 2],
+        prefixed: [3, 4],
+        unprefixed: [-1],
+        name: String::from("abcd"),
+        cookie: Some(vec![5, 6]),
+        bytes: Bytes::from_static(&[7]),
+        rest: Bytes::from_static(&[8, 9, 10]),
+    };
+    let mut buf = BytesMut::new();
+    sequences.serialize(&mut buf).unwrap();
+    assert_eq!(
+        &buf[..],
+        &[
+            2, 1, 2, 2, 3, 4, 0xff, 0xff, 4, b'a', b'b', b'c', b'd', 1, 2, 5, 6, 1, 7, 8, 9, 10
+        ]
+    );
+    assert_eq!(round_trip(&sequences), sequences);
+}
+
+#[test]
+fn test_sequence_bounds() {
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(&[3, 1, 2, 3]);
+    let err = Sequences::deserialize(&mut buf, &Limits::DEFAULT).unwrap_err();
+    assert!(matches!(
+        err.root(),
+        Error::LengthMismatch {
+            expected: 2,
+            actual: 3
+        }
+    ));
+
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(&[
+        2, 1, 2, 2, 3, 4, 0xff, 0xff, 5, b'a', b'b', b'c', b'd', b'e',
+    ]);
+    let err = Sequences::deserialize(&mut buf, &Limits::DEFAULT).unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::Field { item, path, .. } if item == "Sequences" && path == ".name"
+    ));
+    assert!(matches!(
+        err.root(),
+        Error::StringTooLong { length: 5, max: 4 }
+    ));
+}
+
+#[test]
+fn test_sequence_bounds_on_serialize() {
+    let sequences = Sequences {
+        fixed: [1, 2],
+        prefixed: [3, 4],
+        unprefixed: [-1],
+        name: String::from("abcde"),
+        cookie: None,
+        bytes: Bytes::new(),
+        rest: Bytes::new(),
+    };
+    let mut buf = BytesMut::new();
+    let err = sequences.serialize(&mut buf).unwrap_err();
+    assert!(matches!(err, Error::StringTooLong { length: 5, max: 4 }));
+
+    let sequences = Sequences {
+        name: String::from("abcd"),
+        cookie: Some(vec![5, 6, 7]),
+        ..sequences
+    };
+    let err = sequences.serialize(&mut BytesMut::new()).unwrap_err();
+    assert!(matches!(err, Error::ArrayTooLong { length: 3, max: 2 }));
+}
@@ -0,0 +1,89 @@
+use minecrust_protocol::{
+    Deserialize, Error, Limits, Serialize,
+    bytes::{BufMut, BytesMut},
+    datatype::var_int,
+    sequence,
+};
+
+#[test]
+fn test_negative_length() {
+    let mut buf = BytesMut::new();
+    var_int::serialize(&-1, &mut buf).unwrap();
+
+    assert!(matches!(
+        String::deserialize(&mut buf.clone(), &Limits::DEFAULT),
+        Err(Error::NegativeLength(-1))
+    ));
+    assert!(matches!(
+        Vec::<u8>::deserialize(&mut buf, &Limits::DEFAULT),
+        Err(Error::NegativeLength(-1))
+    ));
+}
+
+#[test]
+fn test_string_too_long() {
+    let max = Limits::DEFAULT.max_string_length;
+    let mut buf = BytesMut::new();
+    var_int::serialize(&(max as i32 + 1), &mut buf).unwrap();
+    buf.put_bytes(b'a', max + 1);
+
+    assert!(matches!(
+        String::deserialize(&mut buf, &Limits::DEFAULT),
+        Err(Error::StringTooLong { length, .. }) if length == max + 1
+    ));
+}
+
+#[test]
+fn test_array_too_long() {
+    let mut buf = BytesMut::new();
+    var_int::serialize(&i32::MAX, &mut buf).unwrap();
+
+    assert!(matches!(
+        Vec::<bool>::deserialize(&mut buf, &Limits::DEFAULT),
+        Err(Error::ArrayTooLong { .. })
+    ));
+}
+
+#[derive(Debug, Deserialize)]
+struct Empty;
+
+#[test]
+fn test_unprefixed_array_bounds() {
+    let limits = Limits {
+        max_array_length: 2,
+        ..Limits::DEFAULT
+    };
+    let mut buf = BytesMut::new();
+    buf.put_bytes(0, 3);
+
+    assert!(matches!(
+        sequence::deserialize::<Vec<u8>, _>(&mut buf.clone(), &limits, Some(false), None),
+        Err(Error::ArrayTooLong { length: 3, max: 2 })
+    ));
+    assert!(matches!(
+        sequence::deserialize::<Vec<Empty>, _>(&mut buf, &Limits::DEFAULT, Some(false), None),
+        Err(Error::Custom(_))
+    ));
+}
+
+#[test]
+fn test_nested_limits() {
+    let limits = Limits {
+        max_string_length: 4,
+        ..Limits::DEFAULT
+    };
+    let mut buf = BytesMut::new();
+    vec!["hi".to_string(), "hello".to_string()]
+        .serialize(&mut buf)
+        .unwrap();
+
+    let err = Vec::<String>::deserialize(&mut buf.clone(), &limits).unwrap_err();
+    assert!(matches!(
+        err.root(),
+        Error::StringTooLong { length: 5, max: 4 }
+    ));
+    assert_eq!(
+        Vec::<String>::deserialize(&mut buf, &Limits::DEFAULT).unwrap(),
+        ["hi", "hello"]
+    );
+}
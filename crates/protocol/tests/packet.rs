@@ -0,0 +1,205 @@
+use minecrust_protocol::{
+    Error, Limits, Serialize,
+    bytes::{Bytes, BytesMut},
+    datatype::TextComponent,
+    packet::{
+        RawPacket,
+        v773::{
+            client::{
+                self,
+                configuration::{
+                    ClientboundConfiguration, ServerLink, ServerLinkLabel, ServerLinkType,
+                    ServerLinks, ShowDialog,
+                },
+                status::{ClientboundStatus, PongResponse},
+            },
+            server::{
+                self,
+                configuration::{
+                    ChatMode, ClientInformation, MainHand, ParticleStatus, ServerboundConfiguration,
+                },
+                login::{CustomQueryAnswer, LoginAcknowledged, ServerboundLogin},
+                status::{PingRequest, ServerboundStatus},
+            },
+        },
+    },
+};
+
+#[test]
+fn test_decode_by_id() {
+    let raw_packet = RawPacket {
+        id: 0x01,
+        data: 42i64.to_be_bytes().to_vec().into(),
+    };
+    assert!(matches!(
+        ServerboundStatus::decode(raw_packet, &Limits::DEFAULT),
+        Ok(ServerboundStatus::PingRequest(PingRequest(42)))
+    ));
+
+    let raw_packet = RawPacket::encode(&LoginAcknowledged).unwrap();
+    assert!(matches!(
+        ServerboundLogin::decode(raw_packet, &Limits::DEFAULT),
+        Ok(ServerboundLogin::LoginAcknowledged(_))
+    ));
+}
+
+#[test]
+fn test_decode_clientbound_by_id() {
+    let raw_packet = RawPacket::encode(&PongResponse(42)).unwrap();
+    assert!(matches!(
+        ClientboundStatus::decode(raw_packet, &Limits::DEFAULT),
+        Ok(ClientboundStatus::PongResponse(PongResponse(42)))
+    ));
+
+    let raw_packet = RawPacket::encode(&ServerLinks(vec![ServerLink {
+        label: ServerLinkLabel::BuiltIn(ServerLinkType::Website),
+        url: String::from("a"),
+    }]))
+    .unwrap();
+    let Ok(ClientboundConfiguration::ServerLinks(ServerLinks(links))) =
+        ClientboundConfiguration::decode(raw_packet, &Limits::DEFAULT)
+    else {
+        panic!("expected server links");
+    };
+    assert!(matches!(
+        links[..],
+        [ServerLink {
+            label: ServerLinkLabel::BuiltIn(ServerLinkType::Website),
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_decode_unknown_id() {
+    let raw_packet = RawPacket {
+        id: 0x7f,
+        data: Default::default(),
+    };
+    assert!(matches!(
+        ServerboundStatus::decode(raw_packet, &Limits::DEFAULT),
+        Ok(ServerboundStatus::Unknown(RawPacket { id: 0x7f, .. }))
+    ));
+}
+
+#[test]
+fn test_decode_trailing_bytes() {
+    let raw_packet = RawPacket {
+        id: 0x01,
+        data: vec![0u8; 10].into(),
+    };
+    assert!(matches!(
+        ServerboundStatus::decode(raw_packet.clone(), &Limits::DEFAULT),
+        Ok(ServerboundStatus::PingRequest(_))
+    ));
+
+    let strict = Limits {
+        reject_trailing_bytes: true,
+        ..Limits::DEFAULT
+    };
+    assert!(matches!(
+        ServerboundStatus::decode(raw_packet, &strict),
+        Err(Error::TrailingBytes {
+            id: 0x01,
+            remaining: 2
+        })
+    ));
+}
+
+#[test]
+fn test_decode_client_information() {
+    let raw_packet = RawPacket::encode(&ClientInformation {
+        locale: String::from("en_us"),
+        view_distance: 12,
+        chat_mode: ChatMode::CommandsOnly,
+        chat_colors: true,
+        displayed_skin_parts: 0x7f,
+        main_hand: MainHand::Right,
+        enable_text_filtering: false,
+        allow_server_listings: true,
+        particle_status: ParticleStatus::Minimal,
+    })
+    .unwrap();
+    let Ok(ServerboundConfiguration::ClientInformation(information)) =
+        ServerboundConfiguration::decode(raw_packet, &Limits::DEFAULT)
+    else {
+        panic!("expected client information");
+    };
+    assert_eq!(information.locale, "en_us");
+    assert_eq!(information.chat_mode, ChatMode::CommandsOnly);
+    assert_eq!(information.particle_status, ParticleStatus::Minimal);
+}
+
+#[test]
+fn test_serialize_server_links() {
+    let mut buf = BytesMut::new();
+    ServerLinks(vec![
+        ServerLink {
+            label: ServerLinkLabel::BuiltIn(ServerLinkType::Website),
+            url: String::from("a"),
+        },
+        ServerLink {
+            label: ServerLinkLabel::Custom(Box::new(TextComponent::text("b"))),
+            url: String::from("c"),
+        },
+    ])
+    .serialize(&mut buf)
+    .unwrap();
+    assert_eq!(
+        &buf[..],
+        &[
+            0x02, 0x01, 0x06, 0x01, b'a', 0x00, 0x08, 0x00, 0x01, b'b', 0x01, b'c'
+        ]
+    );
+}
+
+#[test]
+fn test_sequence_wire_format() {
+    let hello = RawPacket::encode(&client::login::Hello {
+        server_id: String::new(),
+        public_key: vec![1],
+        verify_token: [7; 32],
+        should_authenticate: true,
+    })
+    .unwrap();
+    let mut expected = vec![0, 1, 1, 32];
+    expected.extend([7; 32]);
+    expected.push(1);
+    assert_eq!(&hello.data[..], &expected[..]);
+
+    let query = RawPacket::encode(&client::login::CustomQuery {
+        message_id: 5,
+        channel: "a:b".to_string(),
+        data: Bytes::from_static(&[1, 2]),
+    })
+    .unwrap();
+    assert_eq!(&query.data[..], &[5, 3, b'a', b':', b'b', 1, 2]);
+
+    let payload = RawPacket::encode(&client::configuration::CustomPayload {
+        channel: "a:b".to_string(),
+        data: Bytes::from_static(&[1, 2]),
+    })
+    .unwrap();
+    assert_eq!(&payload.data[..], &[3, b'a', b':', b'b', 1, 2]);
+
+    let dialog = RawPacket::encode(&ShowDialog(Bytes::from_static(&[0, 10, 0]))).unwrap();
+    assert_eq!(&dialog.data[..], &[0, 10, 0]);
+
+    let answer: CustomQueryAnswer = RawPacket {
+        id: 0x02,
+        data: Bytes::from_static(&[5, 1, 1, 2]),
+    }
+    .try_into(&Limits::DEFAULT)
+    .unwrap();
+    assert_eq!(answer.message_id, 5);
+    assert_eq!(answer.data.as_deref(), Some(&[1, 2][..]));
+
+    let payload: server::configuration::CustomPayload = RawPacket {
+        id: 0x02,
+        data: Bytes::from_static(&[3, b'a', b':', b'b', 1, 2]),
+    }
+    .try_into(&Limits::DEFAULT)
+    .unwrap();
+    assert_eq!(payload.channel, "a:b");
+    assert_eq!(&payload.data[..], &[1, 2]);
+}
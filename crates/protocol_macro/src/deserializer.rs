@@ -1,85 +1,172 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Data, DeriveInput, Error, Fields};
+use syn::{Data, DeriveInput, Error, Field, Fields, Ident, Path, parse_quote};
 
-use crate::FieldAttributes;
+use crate::{ContainerAttributes, FieldCodec, Repr, add_trait_bounds, variant_discriminants};
 
-pub fn parse_deserialize(input: DeriveInput) -> TokenStream {
-    let fn_body = match parse_deserialize_data(input.data) {
-        Ok(stream) => stream,
+pub fn parse_deserialize(mut input: DeriveInput) -> TokenStream {
+    let (krate, fn_body) = match parse_container(&mut input) {
+        Ok(result) => result,
         Err(err) => return err.into_compile_error(),
     };
 
     let item_name = input.ident;
+    let generics = add_trait_bounds(input.generics, &parse_quote!(#krate::Deserialize));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     quote! {
-        impl crate::Deserialize for #item_name {
-            fn deserialize<B: bytes::Buf>(buf: &mut B) -> Result<Self, crate::Error> {
-                use crate::Deserialize;
+        impl #impl_generics #krate::Deserialize for #item_name #ty_generics #where_clause {
+            fn deserialize<B: #krate::bytes::Buf>(
+                buf: &mut B,
+                limits: &#krate::Limits,
+            ) -> Result<Self, #krate::Error> {
+                use #krate::Deserialize;
+                let __protocol_start = #krate::bytes::Buf::remaining(buf);
                 #fn_body
             }
         }
     }
 }
 
-fn parse_deserialize_data(data: Data) -> Result<TokenStream, Error> {
-    match data {
-        Data::Struct(data) => Ok(match data.fields {
-            Fields::Named(mut fields) => {
-                let struct_contents = fields
-                    .named
-                    .iter_mut()
-                    .map(|field| {
-                        let FieldAttributes { with } = deluxe::extract_attributes(field)?;
-                        let field_ident = &field.ident;
+fn parse_container(input: &mut DeriveInput) -> Result<(Path, TokenStream), Error> {
+    let attributes: ContainerAttributes = deluxe::extract_attributes(input)?;
+    let krate = attributes.crate_path()?;
+    let repr = attributes.repr()?;
+    let fn_body = parse_deserialize_data(&mut input.data, &input.ident, &krate, repr)?;
 
-                        Ok(if let Some(with) = with {
-                            quote! {
-                                #field_ident: #with::deserialize(buf)?,
-                            }
-                        } else {
-                            let field_type = &field.ty;
-                            quote! {
-                                #field_ident: <#field_type>::deserialize(buf)?,
-                            }
-                        })
-                    })
-                    .collect::<Result<Vec<_>, Error>>()?;
-                quote! {
-                    Ok(Self {
-                        #(#struct_contents)*
-                    })
-                }
+    Ok((krate, fn_body))
+}
+
+/// Expression reading the next field value from `buf`, attributing errors to the field.
+fn deserialize_field(
+    field: &mut Field,
+    item: &str,
+    name: &str,
+    krate: &Path,
+) -> Result<TokenStream, Error> {
+    let read = match FieldCodec::extract(field)? {
+        FieldCodec::Default => {
+            let field_type = &field.ty;
+            quote! { <#field_type>::deserialize(buf, limits) }
+        }
+        FieldCodec::With(with) => quote! { #with::deserialize(buf, limits) },
+        FieldCodec::Sequence {
+            prefixed,
+            max_len,
+            optional: false,
+        } => quote! { #krate::sequence::deserialize(buf, limits, #prefixed, #max_len) },
+        FieldCodec::Sequence {
+            prefixed,
+            max_len,
+            optional: true,
+        } => quote! {
+            match <bool as #krate::Deserialize>::deserialize(buf, limits) {
+                Ok(true) => #krate::sequence::deserialize(buf, limits, #prefixed, #max_len)
+                    .map(Some)
+                    .map_err(|err| err.shifted(1)),
+                Ok(false) => Ok(None),
+                Err(err) => Err(err),
             }
-            Fields::Unnamed(mut fields) => {
-                let struct_contents = fields
-                    .unnamed
-                    .iter_mut()
-                    .map(|field| {
-                        let FieldAttributes { with } = deluxe::extract_attributes(field)?;
-                        Ok(if let Some(with) = with {
-                            quote! {
-                                #with::deserialize(buf)?,
-                            }
-                        } else {
-                            let field_type = &field.ty;
-                            quote! {
-                                #field_type::deserialize(buf)?,
-                            }
-                        })
+        },
+    };
+    Ok(quote! {{
+        let offset = __protocol_start - #krate::bytes::Buf::remaining(buf);
+        (#read).map_err(|err| err.in_field(#item, #name, offset))?
+    }})
+}
+
+/// Constructor expression for `Fields`, reading every field in declaration order.
+fn construct(
+    path: TokenStream,
+    item: &str,
+    fields: &mut Fields,
+    krate: &Path,
+) -> Result<TokenStream, Error> {
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let struct_contents = fields
+                .named
+                .iter_mut()
+                .map(|field| {
+                    let field_ident = field.ident.clone();
+                    let name = field_ident
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default();
+                    let value = deserialize_field(field, item, &name, krate)?;
+                    Ok(quote! {
+                        #field_ident: #value,
                     })
-                    .collect::<Result<Vec<_>, Error>>()?;
-                quote! {
-                    Ok(Self(#(#struct_contents)*))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote! {
+                #path {
+                    #(#struct_contents)*
                 }
             }
-            Fields::Unit => {
-                quote! { Ok(Self) }
+        }
+        Fields::Unnamed(fields) => {
+            let struct_contents = fields
+                .unnamed
+                .iter_mut()
+                .enumerate()
+                .map(|(index, field)| deserialize_field(field, item, &index.to_string(), krate))
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote! {
+                #path(#(#struct_contents),*)
             }
-        }),
-        Data::Enum(data) => Err(Error::new(
-            data.enum_token.span,
-            "Deserializing enums is not supported",
-        )),
+        }
+        Fields::Unit => path,
+    })
+}
+
+fn parse_deserialize_data(
+    data: &mut Data,
+    item_name: &Ident,
+    krate: &Path,
+    repr: Repr,
+) -> Result<TokenStream, Error> {
+    match data {
+        Data::Struct(data) => {
+            let value = construct(
+                quote!(Self),
+                &item_name.to_string(),
+                &mut data.fields,
+                krate,
+            )?;
+            Ok(quote! { Ok(#value) })
+        }
+        Data::Enum(data) => {
+            let discriminants = variant_discriminants(data)?;
+            let read_discriminant = repr.deserialize(krate);
+            let arms = data
+                .variants
+                .iter_mut()
+                .zip(discriminants)
+                .map(|(variant, discriminant)| {
+                    let variant_ident = &variant.ident;
+                    let item = format!("{item_name}::{variant_ident}");
+                    let value = construct(
+                        quote!(Self::#variant_ident),
+                        &item,
+                        &mut variant.fields,
+                        krate,
+                    )?;
+                    Ok(quote! {
+                        discriminant if discriminant == (#discriminant) => Ok(#value),
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            Ok(quote! {
+                let discriminant = #read_discriminant;
+                match discriminant {
+                    #(#arms)*
+                    _ => Err(#krate::Error::UnknownDiscriminant {
+                        name: stringify!(#item_name),
+                        value: discriminant.into(),
+                    }),
+                }
+            })
+        }
         Data::Union(data) => Err(Error::new(
             data.union_token.span,
             "Deserializing unions is not supported",
@@ -1,14 +1,198 @@
 use deluxe::ExtractAttributes;
 use proc_macro::TokenStream;
-use syn::{DeriveInput, Path, parse_macro_input};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    DataEnum, DeriveInput, Error, Expr, Field, Generics, Ident, LitStr, Path, Type,
+    parse_macro_input, parse_quote,
+};
 
 mod deserializer;
+mod packet;
 mod serializer;
 
+#[derive(Debug, ExtractAttributes)]
+#[deluxe(attributes(protocol))]
+struct ContainerAttributes {
+    #[deluxe(rename = crate)]
+    krate: Option<LitStr>,
+    repr: Option<Ident>,
+}
+
+impl ContainerAttributes {
+    /// Path under which `minecrust_protocol` is reachable from the deriving crate.
+    fn crate_path(&self) -> syn::Result<Path> {
+        match &self.krate {
+            Some(krate) => krate.parse(),
+            None => Ok(parse_quote!(::minecrust_protocol)),
+        }
+    }
+
+    fn repr(&self) -> syn::Result<Repr> {
+        let Some(repr) = &self.repr else {
+            return Ok(Repr::VarInt);
+        };
+
+        match repr.to_string().as_str() {
+            "var_int" => Ok(Repr::VarInt),
+            "u8" => Ok(Repr::U8),
+            "i32" => Ok(Repr::I32),
+            _ => Err(Error::new(
+                repr.span(),
+                "expected one of `var_int`, `u8` or `i32`",
+            )),
+        }
+    }
+}
+
+#[derive(Debug, ExtractAttributes)]
+#[deluxe(attributes(protocol))]
+struct VariantAttributes {
+    discriminant: Option<Expr>,
+}
+
 #[derive(Debug, ExtractAttributes)]
 #[deluxe(attributes(protocol))]
 struct FieldAttributes {
     with: Option<Path>,
+    prefix: Option<Ident>,
+    rest: deluxe::Flag,
+    max_len: Option<Expr>,
+}
+
+/// How a field is read and written.
+enum FieldCodec {
+    /// Through its `Serialize` and `Deserialize` implementations.
+    Default,
+    /// Through the `serialize(value, buf)` and `deserialize(buf, limits)` functions of a module.
+    With(Path),
+    /// Through `sequence::{serialize, deserialize}` with the given `prefixed` and `max_len`
+    /// arguments, applied to the contained value of `Option` fields.
+    Sequence {
+        prefixed: TokenStream2,
+        max_len: TokenStream2,
+        optional: bool,
+    },
+}
+
+impl FieldCodec {
+    fn extract(field: &mut Field) -> syn::Result<Self> {
+        let FieldAttributes {
+            with,
+            prefix,
+            rest,
+            max_len,
+        } = deluxe::extract_attributes(field)?;
+
+        if prefix.is_none() && !rest.is_set() && max_len.is_none() {
+            return Ok(match with {
+                Some(with) => FieldCodec::With(with),
+                None => FieldCodec::Default,
+            });
+        }
+        if with.is_some() {
+            return Err(Error::new_spanned(
+                &field.ty,
+                "`with` cannot be combined with `prefix`, `rest` or `max_len`",
+            ));
+        }
+
+        let prefixed = match (&prefix, rest.is_set()) {
+            (Some(prefix), true) => {
+                return Err(Error::new(
+                    prefix.span(),
+                    "`prefix` cannot be combined with `rest`",
+                ));
+            }
+            (Some(prefix), false) => match prefix.to_string().as_str() {
+                "var_int" => quote!(Some(true)),
+                "none" => quote!(Some(false)),
+                _ => return Err(Error::new(prefix.span(), "expected `var_int` or `none`")),
+            },
+            (None, true) => quote!(Some(false)),
+            (None, false) => quote!(None),
+        };
+        let max_len = match max_len {
+            Some(max_len) => quote!(Some(#max_len)),
+            None => quote!(None),
+        };
+
+        Ok(FieldCodec::Sequence {
+            prefixed,
+            max_len,
+            optional: is_option(&field.ty),
+        })
+    }
+}
+
+/// Whether `ty` is spelled as an `Option`. Macros only see tokens, so this goes by the last path
+/// segment: an alias of `Option` is not recognized, and any other type named `Option` is. Such
+/// fields cannot use `prefix`, `rest` or `max_len` and need a `with` module instead.
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.qself.is_none()
+        && path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option")
+}
+
+/// Wire representation of an enum discriminant.
+#[derive(Debug, Clone, Copy)]
+enum Repr {
+    VarInt,
+    U8,
+    I32,
+}
+
+impl Repr {
+    fn serialize(self, krate: &Path, discriminant: &Expr) -> TokenStream2 {
+        match self {
+            Repr::VarInt => {
+                quote! { #krate::datatype::var_int::serialize(&(#discriminant), buf)?; }
+            }
+            Repr::U8 => quote! { <u8 as #krate::Serialize>::serialize(&(#discriminant), buf)?; },
+            Repr::I32 => quote! { <i32 as #krate::Serialize>::serialize(&(#discriminant), buf)?; },
+        }
+    }
+
+    fn deserialize(self, krate: &Path) -> TokenStream2 {
+        match self {
+            Repr::VarInt => quote! { #krate::datatype::var_int::deserialize(buf, limits)? },
+            Repr::U8 => quote! { <u8 as #krate::Deserialize>::deserialize(buf, limits)? },
+            Repr::I32 => quote! { <i32 as #krate::Deserialize>::deserialize(buf, limits)? },
+        }
+    }
+}
+
+/// Wire discriminant of every variant, numbered like Rust numbers implicit discriminants.
+fn variant_discriminants(data: &mut DataEnum) -> syn::Result<Vec<Expr>> {
+    let mut previous: Option<Expr> = None;
+    data.variants
+        .iter_mut()
+        .map(|variant| {
+            let VariantAttributes { discriminant } = deluxe::extract_attributes(variant)?;
+            let discriminant = match (discriminant, &variant.discriminant, previous.take()) {
+                (Some(discriminant), _, _) => discriminant,
+                (None, Some((_, discriminant)), _) => discriminant.clone(),
+                (None, None, Some(previous)) => parse_quote!(#previous + 1),
+                (None, None, None) => parse_quote!(0),
+            };
+            previous = Some(discriminant.clone());
+            Ok(discriminant)
+        })
+        .collect()
+}
+
+/// Requires every type parameter to implement `bound`.
+fn add_trait_bounds(mut generics: Generics, bound: &Path) -> Generics {
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
 }
 
 #[proc_macro_derive(Deserialize, attributes(protocol))]
@@ -22,3 +206,9 @@ pub fn derive_serialize(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     serializer::parse_serialize(input).into()
 }
+
+#[proc_macro_derive(Packet, attributes(packet, protocol))]
+pub fn derive_packet(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    packet::parse_packet(input).into()
+}
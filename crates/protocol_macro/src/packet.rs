@@ -0,0 +1,44 @@
+use deluxe::ExtractAttributes;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Expr, Ident, Path};
+
+use crate::ContainerAttributes;
+
+#[derive(Debug, ExtractAttributes)]
+#[deluxe(attributes(packet))]
+struct PacketAttributes {
+    id: Expr,
+    state: Ident,
+    direction: Ident,
+}
+
+pub fn parse_packet(mut input: DeriveInput) -> TokenStream {
+    let (krate, attributes) = match parse_container(&mut input) {
+        Ok(result) => result,
+        Err(err) => return err.into_compile_error(),
+    };
+    let PacketAttributes {
+        id,
+        state,
+        direction,
+    } = attributes;
+
+    let item_name = input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #krate::packet::Packet for #item_name #ty_generics #where_clause {
+            const ID: i32 = #id;
+            const STATE: #krate::packet::State = #krate::packet::State::#state;
+            const DIRECTION: #krate::packet::Direction = #krate::packet::Direction::#direction;
+        }
+    }
+}
+
+fn parse_container(input: &mut DeriveInput) -> Result<(Path, PacketAttributes), Error> {
+    let container: ContainerAttributes = deluxe::extract_attributes(input)?;
+    let krate = container.crate_path()?;
+    let attributes: PacketAttributes = deluxe::extract_attributes(input)?;
+
+    Ok((krate, attributes))
+}
@@ -1,68 +1,98 @@
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Data, DeriveInput, Error, Fields, Index};
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Field, Fields, Index, Path, parse_quote};
 
-use crate::FieldAttributes;
+use crate::{ContainerAttributes, FieldCodec, Repr, add_trait_bounds, variant_discriminants};
 
-pub fn parse_serialize(input: DeriveInput) -> TokenStream {
-    let fn_body = match parse_serialize_data(input.data) {
-        Ok(stream) => stream,
+pub fn parse_serialize(mut input: DeriveInput) -> TokenStream {
+    let (krate, fn_body) = match parse_container(&mut input) {
+        Ok(result) => result,
         Err(err) => return err.into_compile_error(),
     };
 
     let item_name = input.ident;
+    let generics = add_trait_bounds(input.generics, &parse_quote!(#krate::Serialize));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     quote! {
-        impl crate::Serialize for #item_name {
-            fn serialize<B: bytes::BufMut>(&self, buf: &mut B) {
-                use crate::Serialize;
+        impl #impl_generics #krate::Serialize for #item_name #ty_generics #where_clause {
+            fn serialize<B: #krate::bytes::BufMut>(&self, buf: &mut B) -> ::core::result::Result<(), #krate::Error> {
+                use #krate::Serialize;
                 #fn_body
+                ::core::result::Result::Ok(())
             }
         }
     }
 }
 
-fn parse_serialize_data(data: Data) -> Result<TokenStream, Error> {
+fn parse_container(input: &mut DeriveInput) -> Result<(Path, TokenStream), Error> {
+    let attributes: ContainerAttributes = deluxe::extract_attributes(input)?;
+    let krate = attributes.crate_path()?;
+    let repr = attributes.repr()?;
+    let fn_body = parse_serialize_data(&mut input.data, &krate, repr)?;
+
+    Ok((krate, fn_body))
+}
+
+/// Serializes the field behind `value`, which has to be a reference to the field.
+fn serialize_field(
+    field: &mut Field,
+    value: TokenStream,
+    krate: &Path,
+) -> Result<TokenStream, Error> {
+    Ok(match FieldCodec::extract(field)? {
+        FieldCodec::Default => quote! {
+            (#value).serialize(buf)?;
+        },
+        FieldCodec::With(with) => quote! {
+            #with::serialize(#value, buf)?;
+        },
+        FieldCodec::Sequence {
+            prefixed,
+            max_len,
+            optional: false,
+        } => quote! {
+            #krate::sequence::serialize(#value, buf, #prefixed, #max_len)?;
+        },
+        FieldCodec::Sequence {
+            prefixed,
+            max_len,
+            optional: true,
+        } => quote! {
+            match #value {
+                Some(value) => {
+                    <bool as #krate::Serialize>::serialize(&true, buf)?;
+                    #krate::sequence::serialize(value, buf, #prefixed, #max_len)?;
+                }
+                None => <bool as #krate::Serialize>::serialize(&false, buf)?,
+            }
+        },
+    })
+}
+
+fn parse_serialize_data(data: &mut Data, krate: &Path, repr: Repr) -> Result<TokenStream, Error> {
     match data {
-        Data::Struct(data) => Ok(match data.fields {
-            Fields::Named(mut fields) => {
+        Data::Struct(data) => Ok(match &mut data.fields {
+            Fields::Named(fields) => {
                 let statements = fields
                     .named
                     .iter_mut()
                     .map(|field| {
-                        let FieldAttributes { with } = deluxe::extract_attributes(field)?;
-                        let field_ident = &field.ident;
-                        Ok(if let Some(with) = with {
-                            quote! {
-                                #with::serialize(&self.#field_ident, buf);
-                            }
-                        } else {
-                            quote! {
-                                self.#field_ident.serialize(buf);
-                            }
-                        })
+                        let field_ident = field.ident.clone();
+                        serialize_field(field, quote!(&self.#field_ident), krate)
                     })
                     .collect::<Result<Vec<_>, Error>>()?;
                 quote! {
                     #(#statements)*
                 }
             }
-            Fields::Unnamed(mut fields) => {
+            Fields::Unnamed(fields) => {
                 let statements = fields
                     .unnamed
                     .iter_mut()
                     .enumerate()
                     .map(|(index, field)| {
-                        let FieldAttributes { with } = deluxe::extract_attributes(field)?;
                         let field_index = Index::from(index);
-                        Ok(if let Some(with) = with {
-                            quote! {
-                                #with::serialize(&self.#field_index, buf);
-                            }
-                        } else {
-                            quote! {
-                                self.#field_index.serialize(buf);
-                            }
-                        })
+                        serialize_field(field, quote!(&self.#field_index), krate)
                     })
                     .collect::<Result<Vec<_>, Error>>()?;
                 quote! {
@@ -71,10 +101,71 @@ fn parse_serialize_data(data: Data) -> Result<TokenStream, Error> {
             }
             Fields::Unit => quote! {},
         }),
-        Data::Enum(data) => Err(Error::new(
-            data.enum_token.span,
-            "Serializing enums is not supported",
-        )),
+        Data::Enum(data) => {
+            let discriminants = variant_discriminants(data)?;
+            let arms = data
+                .variants
+                .iter_mut()
+                .zip(discriminants)
+                .map(|(variant, discriminant)| {
+                    let variant_ident = &variant.ident;
+                    let write_discriminant = repr.serialize(krate, &discriminant);
+
+                    Ok(match &mut variant.fields {
+                        Fields::Named(fields) => {
+                            let bindings = fields
+                                .named
+                                .iter()
+                                .map(|field| field.ident.clone())
+                                .collect::<Vec<_>>();
+                            let statements = fields
+                                .named
+                                .iter_mut()
+                                .map(|field| {
+                                    let field_ident = field.ident.clone();
+                                    serialize_field(field, quote!(#field_ident), krate)
+                                })
+                                .collect::<Result<Vec<_>, Error>>()?;
+                            quote! {
+                                Self::#variant_ident { #(#bindings),* } => {
+                                    #write_discriminant
+                                    #(#statements)*
+                                }
+                            }
+                        }
+                        Fields::Unnamed(fields) => {
+                            let bindings = (0..fields.unnamed.len())
+                                .map(|index| format_ident!("field_{}", index))
+                                .collect::<Vec<_>>();
+                            let statements = fields
+                                .unnamed
+                                .iter_mut()
+                                .zip(&bindings)
+                                .map(|(field, binding)| {
+                                    serialize_field(field, quote!(#binding), krate)
+                                })
+                                .collect::<Result<Vec<_>, Error>>()?;
+                            quote! {
+                                Self::#variant_ident(#(#bindings),*) => {
+                                    #write_discriminant
+                                    #(#statements)*
+                                }
+                            }
+                        }
+                        Fields::Unit => quote! {
+                            Self::#variant_ident => {
+                                #write_discriminant
+                            }
+                        },
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            Ok(quote! {
+                match self {
+                    #(#arms)*
+                }
+            })
+        }
         Data::Union(data) => Err(Error::new(
             data.union_token.span,
             "Serializing unions is not supported",